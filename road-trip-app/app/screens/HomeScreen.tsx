@@ -80,11 +80,11 @@ export const HomeScreen: React.FC<HomeScreenProps> = ({ navigation }) => {
 
     try {
       setLoading(true);
-      await apiService.joinRoom(joinRoomId.trim());
+      const result = await apiService.joinRoom(joinRoomId.trim());
       await loadRooms();
       setShowJoinModal(false);
       setJoinRoomId('');
-      Alert.alert('Success', 'Joined room successfully');
+      Alert.alert('Success', result.message || 'Joined room successfully');
     } catch (error: any) {
       const errorMessage = error.response?.data?.error || 'Failed to join room';
       Alert.alert('Error', errorMessage);
@@ -212,9 +212,10 @@ export const HomeScreen: React.FC<HomeScreenProps> = ({ navigation }) => {
             <Text style={styles.modalTitle}>Join Room</Text>
             <TextInput
               style={styles.modalInput}
-              placeholder="Enter room ID"
+              placeholder="Enter invite code"
               value={joinRoomId}
               onChangeText={setJoinRoomId}
+              autoCapitalize="characters"
               autoFocus
             />
             <View style={styles.modalButtons}>
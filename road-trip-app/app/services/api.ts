@@ -81,9 +81,9 @@ class ApiService {
     return response.data;
   }
 
-  async joinRoom(roomId: string) {
+  async joinRoom(code: string) {
     const response = await this.client.post('/rooms/join', {
-      room_id: roomId,
+      code,
     });
     return response.data;
   }
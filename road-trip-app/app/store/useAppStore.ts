@@ -57,9 +57,12 @@ export const useAppStore = create<AppStore>((set) => ({
     })),
 
   addMessage: (message) =>
-    set((state) => ({
-      messages: [...state.messages, message],
-    })),
+    set((state) =>
+      // The sender's own message also arrives over the WebSocket
+      state.messages.some((m) => m.id === message.id)
+        ? state
+        : { messages: [...state.messages, message] }
+    ),
 
   setMessages: (messages) => set({ messages }),
 
@@ -23,7 +23,8 @@ export interface Message {
   user_id: string;
   text?: string;
   image_url?: string;
-  message_type: 'text' | 'image';
+  message_type: 'text' | 'image' | 'location' | 'poll' | 'system';
+  payload?: Record<string, unknown>;
   created_at: string;
 }
 
@@ -7,6 +7,29 @@ mod m20240101_000004_create_room_members_table;
 mod m20240101_000005_create_messages_table;
 mod m20240101_000006_create_locations_table;
 mod m20240101_000007_create_voice_calls_table;
+mod m20240101_000008_create_itinerary_stops_table;
+mod m20240101_000009_create_stop_events_table;
+mod m20240101_000010_add_locations_history_index;
+mod m20240101_000011_create_trip_summaries_table;
+mod m20240101_000012_create_trail_compactions_table;
+mod m20240101_000013_create_location_sharing_table;
+mod m20240101_000014_add_locations_geography;
+mod m20240101_000015_create_sos_alerts_table;
+mod m20240101_000016_create_room_invites_table;
+mod m20240101_000017_add_room_member_roles;
+mod m20240101_000018_create_room_bans_table;
+mod m20240101_000019_create_join_requests_table;
+mod m20240101_000020_add_room_max_members;
+mod m20240101_000021_add_room_member_read_state;
+mod m20240101_000022_add_message_replies;
+mod m20240101_000023_create_message_reactions_table;
+mod m20240101_000024_add_messages_text_search;
+mod m20240101_000025_add_message_payload;
+mod m20240101_000026_create_poll_votes_table;
+mod m20240101_000027_create_message_pins_table;
+mod m20240101_000028_create_message_mentions_table;
+mod m20240101_000029_add_itinerary_stops_position_unique;
+mod m20240101_000030_add_room_archived_at;
 
 pub struct Migrator;
 
@@ -21,6 +44,29 @@ impl MigratorTrait for Migrator {
             Box::new(m20240101_000005_create_messages_table::Migration),
             Box::new(m20240101_000006_create_locations_table::Migration),
             Box::new(m20240101_000007_create_voice_calls_table::Migration),
+            Box::new(m20240101_000008_create_itinerary_stops_table::Migration),
+            Box::new(m20240101_000009_create_stop_events_table::Migration),
+            Box::new(m20240101_000010_add_locations_history_index::Migration),
+            Box::new(m20240101_000011_create_trip_summaries_table::Migration),
+            Box::new(m20240101_000012_create_trail_compactions_table::Migration),
+            Box::new(m20240101_000013_create_location_sharing_table::Migration),
+            Box::new(m20240101_000014_add_locations_geography::Migration),
+            Box::new(m20240101_000015_create_sos_alerts_table::Migration),
+            Box::new(m20240101_000016_create_room_invites_table::Migration),
+            Box::new(m20240101_000017_add_room_member_roles::Migration),
+            Box::new(m20240101_000018_create_room_bans_table::Migration),
+            Box::new(m20240101_000019_create_join_requests_table::Migration),
+            Box::new(m20240101_000020_add_room_max_members::Migration),
+            Box::new(m20240101_000021_add_room_member_read_state::Migration),
+            Box::new(m20240101_000022_add_message_replies::Migration),
+            Box::new(m20240101_000023_create_message_reactions_table::Migration),
+            Box::new(m20240101_000024_add_messages_text_search::Migration),
+            Box::new(m20240101_000025_add_message_payload::Migration),
+            Box::new(m20240101_000026_create_poll_votes_table::Migration),
+            Box::new(m20240101_000027_create_message_pins_table::Migration),
+            Box::new(m20240101_000028_create_message_mentions_table::Migration),
+            Box::new(m20240101_000029_add_itinerary_stops_position_unique::Migration),
+            Box::new(m20240101_000030_add_room_archived_at::Migration),
         ]
     }
 }
@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItineraryStop::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ItineraryStop::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ItineraryStop::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(ItineraryStop::Position).integer().not_null())
+                    .col(ColumnDef::new(ItineraryStop::Name).string().not_null())
+                    .col(ColumnDef::new(ItineraryStop::Latitude).double().not_null())
+                    .col(ColumnDef::new(ItineraryStop::Longitude).double().not_null())
+                    .col(
+                        ColumnDef::new(ItineraryStop::Timezone)
+                            .string()
+                            .not_null()
+                            .default("UTC"),
+                    )
+                    .col(ColumnDef::new(ItineraryStop::PlannedArrival).timestamp_with_time_zone())
+                    .col(ColumnDef::new(ItineraryStop::PlannedDeparture).timestamp_with_time_zone())
+                    .col(ColumnDef::new(ItineraryStop::Notes).string())
+                    .col(ColumnDef::new(ItineraryStop::CreatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(ItineraryStop::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ItineraryStop::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_itinerary_stops_room_id")
+                            .from(ItineraryStop::Table, ItineraryStop::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_itinerary_stops_created_by")
+                            .from(ItineraryStop::Table, ItineraryStop::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_itinerary_stops_room_position")
+                    .table(ItineraryStop::Table)
+                    .col(ItineraryStop::RoomId)
+                    .col(ItineraryStop::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ItineraryStop::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ItineraryStop {
+    #[sea_orm(iden = "itinerary_stops")]
+    Table,
+    Id,
+    RoomId,
+    Position,
+    Name,
+    Latitude,
+    Longitude,
+    Timezone,
+    PlannedArrival,
+    PlannedDeparture,
+    Notes,
+    CreatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,109 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StopEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StopEvent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StopEvent::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(StopEvent::StopId).uuid().not_null())
+                    .col(ColumnDef::new(StopEvent::UserId).uuid().not_null())
+                    .col(ColumnDef::new(StopEvent::EventType).string().not_null())
+                    .col(ColumnDef::new(StopEvent::Latitude).double().not_null())
+                    .col(ColumnDef::new(StopEvent::Longitude).double().not_null())
+                    .col(ColumnDef::new(StopEvent::DistanceMeters).double().not_null())
+                    .col(
+                        ColumnDef::new(StopEvent::OccurredAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stop_events_room_id")
+                            .from(StopEvent::Table, StopEvent::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stop_events_stop_id")
+                            .from(StopEvent::Table, StopEvent::StopId)
+                            .to(ItineraryStop::Table, ItineraryStop::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stop_events_user_id")
+                            .from(StopEvent::Table, StopEvent::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stop_events_room_user")
+                    .table(StopEvent::Table)
+                    .col(StopEvent::RoomId)
+                    .col(StopEvent::UserId)
+                    .col(StopEvent::OccurredAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StopEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StopEvent {
+    #[sea_orm(iden = "stop_events")]
+    Table,
+    Id,
+    RoomId,
+    StopId,
+    UserId,
+    EventType,
+    Latitude,
+    Longitude,
+    DistanceMeters,
+    OccurredAt,
+}
+
+#[derive(DeriveIden)]
+enum ItineraryStop {
+    #[sea_orm(iden = "itinerary_stops")]
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
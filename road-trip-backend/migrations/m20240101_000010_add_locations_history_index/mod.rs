@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_locations_room_user_timestamp")
+                    .table(Location::Table)
+                    .col(Location::RoomId)
+                    .col(Location::UserId)
+                    .col((Location::Timestamp, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_locations_room_user_timestamp")
+                    .table(Location::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    #[sea_orm(iden = "locations")]
+    Table,
+    RoomId,
+    UserId,
+    Timestamp,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TripSummary::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TripSummary::RoomId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TripSummary::Summary).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(TripSummary::ComputedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_trip_summaries_room_id")
+                            .from(TripSummary::Table, TripSummary::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TripSummary::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TripSummary {
+    #[sea_orm(iden = "trip_summaries")]
+    Table,
+    RoomId,
+    Summary,
+    ComputedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
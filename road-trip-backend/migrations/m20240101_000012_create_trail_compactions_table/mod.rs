@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TrailCompaction::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TrailCompaction::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(TrailCompaction::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TrailCompaction::CompactedUntil)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TrailCompaction::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TrailCompaction::RoomId)
+                            .col(TrailCompaction::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_trail_compactions_room_id")
+                            .from(TrailCompaction::Table, TrailCompaction::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_trail_compactions_user_id")
+                            .from(TrailCompaction::Table, TrailCompaction::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TrailCompaction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TrailCompaction {
+    #[sea_orm(iden = "trail_compactions")]
+    Table,
+    RoomId,
+    UserId,
+    CompactedUntil,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LocationSharing::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LocationSharing::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(LocationSharing::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(LocationSharing::Paused)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(LocationSharing::PrecisionMeters).double())
+                    .col(ColumnDef::new(LocationSharing::ExpiresAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(LocationSharing::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(LocationSharing::RoomId)
+                            .col(LocationSharing::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_location_sharing_room_id")
+                            .from(LocationSharing::Table, LocationSharing::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_location_sharing_user_id")
+                            .from(LocationSharing::Table, LocationSharing::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LocationSharing::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LocationSharing {
+    #[sea_orm(iden = "location_sharing")]
+    Table,
+    RoomId,
+    UserId,
+    Paused,
+    PrecisionMeters,
+    ExpiresAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
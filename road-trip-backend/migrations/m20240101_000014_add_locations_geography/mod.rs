@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // PostGIS is optional; without it spatial queries use haversine
+        if db
+            .execute_unprepared("CREATE EXTENSION IF NOT EXISTS postgis")
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        db.execute_unprepared(
+            "ALTER TABLE locations ADD COLUMN IF NOT EXISTS geog geography(Point, 4326)",
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION locations_set_geog() RETURNS trigger AS $$
+            BEGIN
+                NEW.geog := ST_SetSRID(ST_MakePoint(NEW.longitude, NEW.latitude), 4326)::geography;
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql
+            "#,
+        )
+        .await?;
+        db.execute_unprepared("DROP TRIGGER IF EXISTS trg_locations_set_geog ON locations")
+            .await?;
+        db.execute_unprepared(
+            "CREATE TRIGGER trg_locations_set_geog BEFORE INSERT OR UPDATE OF latitude, longitude ON locations FOR EACH ROW EXECUTE FUNCTION locations_set_geog()",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE locations SET geog = ST_SetSRID(ST_MakePoint(longitude, latitude), 4326)::geography WHERE geog IS NULL",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_locations_geog ON locations USING GIST (geog)",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP TRIGGER IF EXISTS trg_locations_set_geog ON locations")
+            .await?;
+        db.execute_unprepared("DROP FUNCTION IF EXISTS locations_set_geog()")
+            .await?;
+        db.execute_unprepared("ALTER TABLE locations DROP COLUMN IF EXISTS geog")
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SosAlert::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(SosAlert::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(SosAlert::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(SosAlert::UserId).uuid().not_null())
+                    .col(ColumnDef::new(SosAlert::Note).text())
+                    .col(ColumnDef::new(SosAlert::Latitude).double())
+                    .col(ColumnDef::new(SosAlert::Longitude).double())
+                    .col(ColumnDef::new(SosAlert::LocatedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(SosAlert::MessageId).uuid())
+                    .col(
+                        ColumnDef::new(SosAlert::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SosAlert::AcknowledgedBy).uuid())
+                    .col(ColumnDef::new(SosAlert::AcknowledgedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sos_alerts_room_id")
+                            .from(SosAlert::Table, SosAlert::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sos_alerts_user_id")
+                            .from(SosAlert::Table, SosAlert::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sos_alerts_message_id")
+                            .from(SosAlert::Table, SosAlert::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_sos_alerts_acknowledged_by")
+                            .from(SosAlert::Table, SosAlert::AcknowledgedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Partial index: only unacknowledged alerts are looked up
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX IF NOT EXISTS idx_sos_alerts_active ON sos_alerts(room_id) WHERE acknowledged_at IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SosAlert::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SosAlert {
+    #[sea_orm(iden = "sos_alerts")]
+    Table,
+    Id,
+    RoomId,
+    UserId,
+    Note,
+    Latitude,
+    Longitude,
+    LocatedAt,
+    MessageId,
+    CreatedAt,
+    AcknowledgedBy,
+    AcknowledgedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoomInvite::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RoomInvite::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(RoomInvite::RoomId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RoomInvite::Code)
+                            .string_len(32)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(RoomInvite::CreatedBy).uuid().not_null())
+                    .col(
+                        ColumnDef::new(RoomInvite::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RoomInvite::ExpiresAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(RoomInvite::MaxUses).integer())
+                    .col(
+                        ColumnDef::new(RoomInvite::Uses)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(RoomInvite::RevokedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_room_invites_room_id")
+                            .from(RoomInvite::Table, RoomInvite::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_room_invites_created_by")
+                            .from(RoomInvite::Table, RoomInvite::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_room_invites_room_id")
+                    .table(RoomInvite::Table)
+                    .col(RoomInvite::RoomId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoomInvite::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoomInvite {
+    #[sea_orm(iden = "room_invites")]
+    Table,
+    Id,
+    RoomId,
+    Code,
+    CreatedBy,
+    CreatedAt,
+    ExpiresAt,
+    MaxUses,
+    Uses,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
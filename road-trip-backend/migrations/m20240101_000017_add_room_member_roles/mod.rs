@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RoomMember::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(RoomMember::Role)
+                            .string_len(16)
+                            .not_null()
+                            .default("member"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Room creators become owners of their existing rooms
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE room_members rm SET role = 'owner'
+                FROM rooms r
+                WHERE rm.room_id = r.id AND rm.user_id = r.created_by
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RoomMember::Table)
+                    .drop_column(RoomMember::Role)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoomMember {
+    #[sea_orm(iden = "room_members")]
+    Table,
+    Role,
+}
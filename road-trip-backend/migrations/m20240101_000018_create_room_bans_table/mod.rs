@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoomBan::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RoomBan::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(RoomBan::UserId).uuid().not_null())
+                    .col(ColumnDef::new(RoomBan::BannedBy).uuid().not_null())
+                    .col(ColumnDef::new(RoomBan::Reason).text())
+                    .col(
+                        ColumnDef::new(RoomBan::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(RoomBan::RoomId)
+                            .col(RoomBan::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_room_bans_room_id")
+                            .from(RoomBan::Table, RoomBan::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_room_bans_user_id")
+                            .from(RoomBan::Table, RoomBan::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_room_bans_banned_by")
+                            .from(RoomBan::Table, RoomBan::BannedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoomBan::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoomBan {
+    #[sea_orm(iden = "room_bans")]
+    Table,
+    RoomId,
+    UserId,
+    BannedBy,
+    Reason,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Room::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Room::ApprovalRequired)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(JoinRequest::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(JoinRequest::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(JoinRequest::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(JoinRequest::UserId).uuid().not_null())
+                    .col(ColumnDef::new(JoinRequest::InviteId).uuid())
+                    .col(
+                        ColumnDef::new(JoinRequest::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(JoinRequest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(JoinRequest::DecidedBy).uuid())
+                    .col(ColumnDef::new(JoinRequest::DecidedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_requests_room_id")
+                            .from(JoinRequest::Table, JoinRequest::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_requests_user_id")
+                            .from(JoinRequest::Table, JoinRequest::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_requests_invite_id")
+                            .from(JoinRequest::Table, JoinRequest::InviteId)
+                            .to(RoomInvite::Table, RoomInvite::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_join_requests_decided_by")
+                            .from(JoinRequest::Table, JoinRequest::DecidedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // At most one pending request per user and room
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_join_requests_pending ON join_requests(room_id, user_id) WHERE status = 'pending'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JoinRequest::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Room::Table)
+                    .drop_column(Room::ApprovalRequired)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JoinRequest {
+    #[sea_orm(iden = "join_requests")]
+    Table,
+    Id,
+    RoomId,
+    UserId,
+    InviteId,
+    Status,
+    CreatedAt,
+    DecidedBy,
+    DecidedAt,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+    ApprovalRequired,
+}
+
+#[derive(DeriveIden)]
+enum RoomInvite {
+    #[sea_orm(iden = "room_invites")]
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RoomMember::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RoomMember::LastReadMessageId).uuid())
+                    .add_column_if_not_exists(
+                        ColumnDef::new(RoomMember::LastReadAt).timestamp_with_time_zone(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_room_members_last_read_message_id")
+                            .from_tbl(RoomMember::Table)
+                            .from_col(RoomMember::LastReadMessageId)
+                            .to_tbl(Message::Table)
+                            .to_col(Message::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RoomMember::Table)
+                    .drop_column(RoomMember::LastReadMessageId)
+                    .drop_column(RoomMember::LastReadAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoomMember {
+    #[sea_orm(iden = "room_members")]
+    Table,
+    LastReadMessageId,
+    LastReadAt,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
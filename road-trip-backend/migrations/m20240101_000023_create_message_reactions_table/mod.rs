@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageReaction::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MessageReaction::MessageId).uuid().not_null())
+                    .col(ColumnDef::new(MessageReaction::UserId).uuid().not_null())
+                    .col(ColumnDef::new(MessageReaction::Emoji).string_len(32).not_null())
+                    .col(
+                        ColumnDef::new(MessageReaction::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MessageReaction::MessageId)
+                            .col(MessageReaction::UserId)
+                            .col(MessageReaction::Emoji),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_reactions_message_id")
+                            .from(MessageReaction::Table, MessageReaction::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_reactions_user_id")
+                            .from(MessageReaction::Table, MessageReaction::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageReaction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MessageReaction {
+    #[sea_orm(iden = "message_reactions")]
+    Table,
+    MessageId,
+    UserId,
+    Emoji,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PollVote::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PollVote::MessageId).uuid().not_null())
+                    .col(ColumnDef::new(PollVote::UserId).uuid().not_null())
+                    .col(ColumnDef::new(PollVote::OptionIndex).integer().not_null())
+                    .col(
+                        ColumnDef::new(PollVote::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PollVote::MessageId)
+                            .col(PollVote::UserId)
+                            .col(PollVote::OptionIndex),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_poll_votes_message_id")
+                            .from(PollVote::Table, PollVote::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_poll_votes_user_id")
+                            .from(PollVote::Table, PollVote::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PollVote::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PollVote {
+    #[sea_orm(iden = "poll_votes")]
+    Table,
+    MessageId,
+    UserId,
+    OptionIndex,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
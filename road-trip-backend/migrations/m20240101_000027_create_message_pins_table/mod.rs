@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessagePin::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MessagePin::MessageId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MessagePin::RoomId).uuid().not_null())
+                    .col(ColumnDef::new(MessagePin::PinnedBy).uuid())
+                    .col(
+                        ColumnDef::new(MessagePin::PinnedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_pins_message_id")
+                            .from(MessagePin::Table, MessagePin::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_pins_room_id")
+                            .from(MessagePin::Table, MessagePin::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_pins_pinned_by")
+                            .from(MessagePin::Table, MessagePin::PinnedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_message_pins_room_id")
+                    .table(MessagePin::Table)
+                    .col(MessagePin::RoomId)
+                    .col(MessagePin::PinnedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessagePin::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MessagePin {
+    #[sea_orm(iden = "message_pins")]
+    Table,
+    MessageId,
+    RoomId,
+    PinnedBy,
+    PinnedAt,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageMention::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MessageMention::MessageId).uuid().not_null())
+                    .col(ColumnDef::new(MessageMention::UserId).uuid().not_null())
+                    .col(ColumnDef::new(MessageMention::RoomId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MessageMention::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MessageMention::MessageId)
+                            .col(MessageMention::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_mentions_message_id")
+                            .from(MessageMention::Table, MessageMention::MessageId)
+                            .to(Message::Table, Message::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_mentions_user_id")
+                            .from(MessageMention::Table, MessageMention::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_message_mentions_room_id")
+                            .from(MessageMention::Table, MessageMention::RoomId)
+                            .to(Room::Table, Room::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_message_mentions_user_id")
+                    .table(MessageMention::Table)
+                    .col(MessageMention::UserId)
+                    .col(MessageMention::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MessageMention::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MessageMention {
+    #[sea_orm(iden = "message_mentions")]
+    Table,
+    MessageId,
+    UserId,
+    RoomId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Message {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Room {
+    Table,
+    Id,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigration)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Renumber any duplicate positions before the constraint can be added
+        db.execute_unprepared(
+            r#"
+            UPDATE itinerary_stops s
+            SET position = r.position
+            FROM (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY room_id ORDER BY position, created_at) - 1 AS position
+                FROM itinerary_stops
+            ) r
+            WHERE s.id = r.id AND s.position <> r.position
+            "#,
+        )
+        .await?;
+
+        // Deferred so a reorder can move stops through each other's positions
+        db.execute_unprepared(
+            "ALTER TABLE itinerary_stops ADD CONSTRAINT uq_itinerary_stops_room_position UNIQUE (room_id, position) DEFERRABLE INITIALLY DEFERRED",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE itinerary_stops DROP CONSTRAINT IF EXISTS uq_itinerary_stops_room_position",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
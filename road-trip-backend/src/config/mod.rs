@@ -10,6 +10,8 @@ pub struct Config {
     pub auth: AuthConfig,
     pub cors: CorsConfig,
     pub upload: UploadConfig,
+    pub location: LocationConfig,
+    pub rooms: RoomConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -45,6 +47,30 @@ pub struct UploadConfig {
     pub max_file_size: u64,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocationConfig {
+    pub geofence_radius_meters: f64,
+    pub convoy_max_spread_meters: f64,
+    pub trail_compaction_age_hours: i64,
+    pub trail_compaction_tolerance_meters: f64,
+    pub trail_compaction_interval_minutes: u64,
+    /// Days after a room is archived before its raw location history is purged
+    pub retention_days_after_archive: i64,
+    pub retention_interval_minutes: u64,
+    /// Report what would be purged without deleting anything
+    pub retention_dry_run: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomConfig {
+    /// Invite codes are appended to this to build shareable join links
+    pub invite_link_base_url: Option<String>,
+    /// Members allowed per room; rooms may set a lower limit of their own
+    pub max_members: u64,
+    /// Active (non-archived) rooms a user may own at once
+    pub max_active_rooms_per_user: u64,
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, anyhow::Error> {
         Ok(Config {
@@ -95,6 +121,53 @@ impl Config {
                     .parse()
                     .unwrap_or(10_485_760), // 10MB default
             },
+            location: LocationConfig {
+                geofence_radius_meters: env::var("GEOFENCE_RADIUS_METERS")
+                    .unwrap_or_else(|_| "150".to_string())
+                    .parse()
+                    .unwrap_or(150.0),
+                convoy_max_spread_meters: env::var("CONVOY_MAX_SPREAD_METERS")
+                    .unwrap_or_else(|_| "3000".to_string())
+                    .parse()
+                    .unwrap_or(3000.0),
+                trail_compaction_age_hours: env::var("TRAIL_COMPACTION_AGE_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()
+                    .unwrap_or(24),
+                trail_compaction_tolerance_meters: env::var("TRAIL_COMPACTION_TOLERANCE_METERS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10.0),
+                trail_compaction_interval_minutes: env::var("TRAIL_COMPACTION_INTERVAL_MINUTES")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()
+                    .unwrap_or(60),
+                retention_days_after_archive: env::var("LOCATION_RETENTION_DAYS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+                retention_interval_minutes: env::var("LOCATION_RETENTION_INTERVAL_MINUTES")
+                    .unwrap_or_else(|_| "1440".to_string())
+                    .parse()
+                    .unwrap_or(1440),
+                retention_dry_run: env::var("LOCATION_RETENTION_DRY_RUN")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+            },
+            rooms: RoomConfig {
+                invite_link_base_url: env::var("INVITE_LINK_BASE_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty()),
+                max_members: env::var("ROOM_MAX_MEMBERS")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()
+                    .unwrap_or(50),
+                max_active_rooms_per_user: env::var("USER_MAX_ACTIVE_ROOMS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+            },
         })
     }
 }
@@ -0,0 +1,57 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A member's location sharing preferences within one room. Members without
+/// a row share their exact position indefinitely.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "location_sharing")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub room_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: Uuid,
+    pub paused: bool,
+    /// Grid size positions are snapped to before sharing; `None` shares exact fixes
+    #[sea_orm(column_type = "Double", nullable)]
+    pub precision_meters: Option<f64>,
+    /// Sharing stops automatically after this time
+    pub expires_at: Option<DateTimeUtc>,
+    pub updated_at: DateTimeUtc,
+}
+
+impl Model {
+    pub fn is_sharing(&self, now: DateTimeUtc) -> bool {
+        !self.paused && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::room::Entity",
+        from = "Column::RoomId",
+        to = "super::room::Column::Id"
+    )]
+    Room,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::room::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Room.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,12 +1,89 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MessageType {
     #[serde(rename = "text")]
     Text,
     #[serde(rename = "image")]
     Image,
+    /// A shared map pin; payload is a [`LocationPin`]
+    #[serde(rename = "location")]
+    Location,
+    /// Payload is a [`PollPayload`]
+    #[serde(rename = "poll")]
+    Poll,
+    /// Generated by the server; payload is a [`SystemEvent`]
+    #[serde(rename = "system")]
+    System,
+}
+
+impl MessageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageType::Text => "text",
+            MessageType::Image => "image",
+            MessageType::Location => "location",
+            MessageType::Poll => "poll",
+            MessageType::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(MessageType::Text),
+            "image" => Some(MessageType::Image),
+            "location" => Some(MessageType::Location),
+            "poll" => Some(MessageType::Poll),
+            "system" => Some(MessageType::System),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocationPin {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PollPayload {
+    pub question: String,
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub multiple_choice: bool,
+    /// Voting stops at this time when set
+    pub closes_at: Option<DateTimeUtc>,
+}
+
+/// What a system message announces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum SystemEvent {
+    MemberJoined {
+        user_id: Uuid,
+    },
+    MemberLeft {
+        user_id: Uuid,
+        /// Set when an admin removed the member
+        removed_by: Option<Uuid>,
+    },
+    ArrivedAtStop {
+        user_id: Uuid,
+        stop_id: Uuid,
+    },
+    DepartedStop {
+        user_id: Uuid,
+        stop_id: Uuid,
+    },
+    Sos {
+        user_id: Uuid,
+        sos_id: Uuid,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -20,6 +97,10 @@ pub struct Model {
     pub image_url: Option<String>,
     pub message_type: String,
     pub created_at: DateTimeUtc,
+    /// Message this one replies to, within the same room
+    pub reply_to_id: Option<Uuid>,
+    /// Structured content for location, poll and system messages
+    pub payload: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
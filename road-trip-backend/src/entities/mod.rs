@@ -5,6 +5,19 @@ pub mod room_member;
 pub mod user;
 pub mod voice_call;
 pub mod session;
+pub mod itinerary_stop;
+pub mod stop_event;
+pub mod trip_summary;
+pub mod trail_compaction;
+pub mod location_sharing;
+pub mod sos_alert;
+pub mod message_pin;
+pub mod room_invite;
+pub mod room_ban;
+pub mod join_request;
+pub mod message_reaction;
+pub mod poll_vote;
+pub mod message_mention;
 
 pub use message::Entity as Message;
 pub use location::Entity as Location;
@@ -13,3 +26,16 @@ pub use room_member::Entity as RoomMember;
 pub use user::Entity as User;
 pub use voice_call::Entity as VoiceCall;
 pub use session::Entity as Session;
+pub use itinerary_stop::Entity as ItineraryStop;
+pub use stop_event::Entity as StopEvent;
+pub use trip_summary::Entity as TripSummary;
+pub use trail_compaction::Entity as TrailCompaction;
+pub use location_sharing::Entity as LocationSharing;
+pub use sos_alert::Entity as SosAlert;
+pub use message_pin::Entity as MessagePin;
+pub use room_invite::Entity as RoomInvite;
+pub use room_ban::Entity as RoomBan;
+pub use join_request::Entity as JoinRequest;
+pub use message_reaction::Entity as MessageReaction;
+pub use poll_vote::Entity as PollVote;
+pub use message_mention::Entity as MessageMention;
@@ -1,7 +1,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "rooms")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -12,6 +12,19 @@ pub struct Model {
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
     pub is_active: bool,
+    /// Maximum distance a member may drift from the convoy before an alert;
+    /// falls back to the server default when unset
+    #[sea_orm(column_type = "Double", nullable)]
+    pub max_spread_meters: Option<f64>,
+    /// Member whose position anchors the convoy; the centroid is used when unset
+    pub convoy_leader_id: Option<Uuid>,
+    /// Invite codes create a join request for an admin to approve instead
+    /// of adding the member directly
+    pub approval_required: bool,
+    /// Room-specific member cap below the server-wide limit
+    pub max_members: Option<i32>,
+    /// When the room was last archived; unset while it is active
+    pub archived_at: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -34,6 +47,9 @@ pub enum Relation {
     
     #[sea_orm(has_many = "super::voice_call::Entity")]
     VoiceCalls,
+    
+    #[sea_orm(has_many = "super::itinerary_stop::Entity")]
+    ItineraryStops,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -66,4 +82,10 @@ impl Related<super::voice_call::Entity> for Entity {
     }
 }
 
+impl Related<super::itinerary_stop::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ItineraryStops.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,36 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Roles are ordered by privilege: `Member < Admin < Owner`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RoomRole {
+    #[serde(rename = "member")]
+    Member,
+    #[serde(rename = "admin")]
+    Admin,
+    #[serde(rename = "owner")]
+    Owner,
+}
+
+impl RoomRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoomRole::Member => "member",
+            RoomRole::Admin => "admin",
+            RoomRole::Owner => "owner",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "member" => Some(RoomRole::Member),
+            "admin" => Some(RoomRole::Admin),
+            "owner" => Some(RoomRole::Owner),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "room_members")]
 pub struct Model {
@@ -9,6 +39,26 @@ pub struct Model {
     pub room_id: Uuid,
     pub user_id: Uuid,
     pub joined_at: DateTimeUtc,
+    pub role: String,
+    pub last_read_message_id: Option<Uuid>,
+    /// Creation time of the last read message; later messages are unread
+    pub last_read_at: Option<DateTimeUtc>,
+}
+
+impl Model {
+    /// Unknown role values are treated as plain membership
+    pub fn room_role(&self) -> RoomRole {
+        RoomRole::parse(&self.role).unwrap_or(RoomRole::Member)
+    }
+
+    /// Messages after this point, in (created_at, id) order, count as unread.
+    /// Before anything was read only messages newer than the join count.
+    pub fn read_cursor(&self) -> (DateTimeUtc, Option<Uuid>) {
+        match self.last_read_at {
+            Some(read_at) => (read_at, self.last_read_message_id),
+            None => (self.joined_at, None),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,81 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopEventType {
+    #[serde(rename = "arrived")]
+    Arrived,
+    #[serde(rename = "departed")]
+    Departed,
+}
+
+impl StopEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopEventType::Arrived => "arrived",
+            StopEventType::Departed => "departed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "stop_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub stop_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    #[sea_orm(column_type = "Double")]
+    pub latitude: f64,
+    #[sea_orm(column_type = "Double")]
+    pub longitude: f64,
+    #[sea_orm(column_type = "Double")]
+    pub distance_meters: f64,
+    pub occurred_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::room::Entity",
+        from = "Column::RoomId",
+        to = "super::room::Column::Id"
+    )]
+    Room,
+
+    #[sea_orm(
+        belongs_to = "super::itinerary_stop::Entity",
+        from = "Column::StopId",
+        to = "super::itinerary_stop::Column::Id"
+    )]
+    Stop,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::room::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Room.def()
+    }
+}
+
+impl Related<super::itinerary_stop::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stop.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,91 @@
+use axum::{http::StatusCode, Json};
+use uuid::Uuid;
+
+use crate::entities::room_member::RoomRole;
+use crate::services::permissions::Permission;
+
+/// Maps a service error to a status code based on its message
+pub(crate) fn error_response(e: anyhow::Error) -> (StatusCode, Json<serde_json::Value>) {
+    let error_msg = format!("{}", e);
+    let status = if error_msg.contains("not found") {
+        StatusCode::NOT_FOUND
+    } else if error_msg.starts_with("Invalid") {
+        StatusCode::BAD_REQUEST
+    } else if error_msg.starts_with("Forbidden") || error_msg.contains("banned") {
+        StatusCode::FORBIDDEN
+    } else if error_msg.contains("archived")
+        || error_msg.contains("is closed")
+        || error_msg.starts_with("Limit exceeded")
+    {
+        StatusCode::CONFLICT
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, Json(serde_json::json!({"error": error_msg})))
+}
+
+pub(crate) async fn require_member(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let is_member = app_state.room_service
+        .is_member(room_id, user_id)
+        .await
+        .map_err(error_response)?;
+
+    if !is_member {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "You are not a member of this room"})),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The caller's role in the room; 403 if they are not a member
+pub(crate) async fn require_role(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    user_id: Uuid,
+) -> Result<RoomRole, (StatusCode, Json<serde_json::Value>)> {
+    let role = app_state.room_service
+        .get_role(room_id, user_id)
+        .await
+        .map_err(error_response)?;
+
+    role.ok_or_else(|| {
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({"error": "You are not a member of this room"})),
+        )
+    })
+}
+
+pub(crate) async fn require_permission(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    user_id: Uuid,
+    permission: Permission,
+) -> Result<RoomRole, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_role(app_state, room_id, user_id).await?;
+    check_permission(role, permission)?;
+    Ok(role)
+}
+
+pub(crate) fn check_permission(
+    role: RoomRole,
+    permission: Permission,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if !permission.allows(role) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": format!("Your role does not allow you to {}", permission.describe())
+            })),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::user;
+use crate::handlers::access::{error_response, require_member, require_permission};
+use crate::services::permissions::Permission;
+use crate::handlers::room::RoomResponse;
+use crate::services::location_service::ConvoyMemberStatus;
+
+#[derive(Deserialize)]
+pub struct UpdateConvoySettingsRequest {
+    /// `null` resets the room to the server default
+    pub max_spread_meters: Option<f64>,
+    pub leader_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct ConvoyMemberResponse {
+    pub user_id: Uuid,
+    pub distance_meters: f64,
+    pub separated: bool,
+}
+
+#[derive(Serialize)]
+pub struct ConvoyStatusResponse {
+    pub room_id: Uuid,
+    pub max_spread_meters: f64,
+    pub members: Vec<ConvoyMemberResponse>,
+}
+
+impl From<ConvoyMemberStatus> for ConvoyMemberResponse {
+    fn from(status: ConvoyMemberStatus) -> Self {
+        Self {
+            user_id: status.user_id,
+            distance_meters: status.distance_meters,
+            separated: status.separated,
+        }
+    }
+}
+
+pub async fn get_convoy_status(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<ConvoyStatusResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let (max_spread_meters, members) = app_state.location_service
+        .get_convoy_status(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(ConvoyStatusResponse {
+        room_id,
+        max_spread_meters,
+        members: members.into_iter().map(ConvoyMemberResponse::from).collect(),
+    }))
+}
+
+pub async fn update_convoy_settings(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<UpdateConvoySettingsRequest>,
+) -> Result<Json<RoomResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ManageConvoy).await?;
+
+    let room = app_state.room_service
+        .update_convoy_settings(room_id, payload.max_spread_meters, payload.leader_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(RoomResponse::from(room)))
+}
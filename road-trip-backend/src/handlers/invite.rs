@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{room_invite, user};
+use crate::handlers::access::{error_response, require_permission};
+use crate::services::permissions::Permission;
+
+#[derive(Deserialize)]
+pub struct CreateInviteRequest {
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// `null` allows unlimited uses
+    pub max_uses: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct InviteResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub code: String,
+    /// Shareable join link, when the server has a link base URL configured
+    pub link: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub active: bool,
+}
+
+#[derive(Serialize)]
+pub struct InvitesResponse {
+    pub invites: Vec<InviteResponse>,
+}
+
+fn invite_response(app_state: &crate::routes::AppState, invite: room_invite::Model) -> InviteResponse {
+    InviteResponse {
+        link: app_state.room_service.invite_link(&invite.code),
+        active: invite.unusable_reason(chrono::Utc::now()).is_none(),
+        id: invite.id,
+        room_id: invite.room_id,
+        code: invite.code,
+        created_by: invite.created_by,
+        created_at: invite.created_at,
+        expires_at: invite.expires_at,
+        max_uses: invite.max_uses,
+        uses: invite.uses,
+        revoked_at: invite.revoked_at,
+    }
+}
+
+pub async fn create_invite(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<CreateInviteRequest>,
+) -> Result<Json<InviteResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ManageInvites).await?;
+
+    let invite = app_state.room_service
+        .create_invite(room_id, user.id, payload.expires_at, payload.max_uses)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(invite_response(&app_state, invite)))
+}
+
+pub async fn get_invites(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<InvitesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ManageInvites).await?;
+
+    let invites = app_state.room_service
+        .get_invites(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(InvitesResponse {
+        invites: invites
+            .into_iter()
+            .map(|invite| invite_response(&app_state, invite))
+            .collect(),
+    }))
+}
+
+pub async fn revoke_invite(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, invite_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<InviteResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ManageInvites).await?;
+
+    let invite = app_state.room_service
+        .revoke_invite(room_id, invite_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(invite_response(&app_state, invite)))
+}
@@ -0,0 +1,257 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{itinerary_stop, stop_event};
+use crate::entities::user;
+use crate::handlers::access::{error_response, require_member, require_permission};
+use crate::services::permissions::Permission;
+use crate::services::itinerary_service::{to_local_time, StopInput};
+use crate::services::websocket::WebSocketEvent;
+
+#[derive(Deserialize)]
+pub struct StopRequest {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: String,
+    /// Local wall-clock time at the stop, e.g. "2024-07-04T14:30:00"
+    pub planned_arrival: Option<chrono::NaiveDateTime>,
+    pub planned_departure: Option<chrono::NaiveDateTime>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReorderStopsRequest {
+    pub stop_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct StopResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub position: i32,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: String,
+    pub planned_arrival: Option<chrono::DateTime<chrono::Utc>>,
+    pub planned_arrival_local: Option<String>,
+    pub planned_departure: Option<chrono::DateTime<chrono::Utc>>,
+    pub planned_departure_local: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ItineraryResponse {
+    pub stops: Vec<StopResponse>,
+}
+
+#[derive(Serialize)]
+pub struct StopEventResponse {
+    pub id: Uuid,
+    pub stop_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub distance_meters: f64,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize)]
+pub struct StopEventsResponse {
+    pub events: Vec<StopEventResponse>,
+}
+
+impl From<stop_event::Model> for StopEventResponse {
+    fn from(event: stop_event::Model) -> Self {
+        Self {
+            id: event.id,
+            stop_id: event.stop_id,
+            user_id: event.user_id,
+            event_type: event.event_type,
+            latitude: event.latitude,
+            longitude: event.longitude,
+            distance_meters: event.distance_meters,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+
+impl From<StopRequest> for StopInput {
+    fn from(req: StopRequest) -> Self {
+        Self {
+            name: req.name,
+            latitude: req.latitude,
+            longitude: req.longitude,
+            timezone: req.timezone,
+            planned_arrival: req.planned_arrival,
+            planned_departure: req.planned_departure,
+            notes: req.notes,
+        }
+    }
+}
+
+impl From<itinerary_stop::Model> for StopResponse {
+    fn from(stop: itinerary_stop::Model) -> Self {
+        Self {
+            planned_arrival_local: stop
+                .planned_arrival
+                .map(|t| to_local_time(t, &stop.timezone)),
+            planned_departure_local: stop
+                .planned_departure
+                .map(|t| to_local_time(t, &stop.timezone)),
+            id: stop.id,
+            room_id: stop.room_id,
+            position: stop.position,
+            name: stop.name,
+            latitude: stop.latitude,
+            longitude: stop.longitude,
+            timezone: stop.timezone,
+            planned_arrival: stop.planned_arrival,
+            planned_departure: stop.planned_departure,
+            notes: stop.notes,
+            created_by: stop.created_by,
+            created_at: stop.created_at,
+            updated_at: stop.updated_at,
+        }
+    }
+}
+
+async fn broadcast_itinerary_updated(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    user_id: Uuid,
+) {
+    let stop_ids = match app_state.itinerary_service.get_stops(room_id).await {
+        Ok(stops) => stops.into_iter().map(|s| s.id).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load itinerary for broadcast: {}", e);
+            return;
+        }
+    };
+
+    let event = WebSocketEvent::ItineraryUpdated {
+        room_id,
+        user_id,
+        stop_ids,
+    };
+    if let Err(e) = app_state.websocket_service.broadcast_to_room(room_id, event).await {
+        tracing::warn!("Failed to broadcast itinerary update: {}", e);
+    }
+}
+
+pub async fn get_itinerary(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<ItineraryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let stops = app_state.itinerary_service
+        .get_stops(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(ItineraryResponse {
+        stops: stops.into_iter().map(StopResponse::from).collect(),
+    }))
+}
+
+pub async fn create_stop(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<StopRequest>,
+) -> Result<Json<StopResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::EditItinerary).await?;
+
+    let stop = app_state.itinerary_service
+        .create_stop(room_id, user.id, payload.into())
+        .await
+        .map_err(error_response)?;
+
+    broadcast_itinerary_updated(&app_state, room_id, user.id).await;
+
+    Ok(Json(StopResponse::from(stop)))
+}
+
+pub async fn update_stop(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, stop_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<StopRequest>,
+) -> Result<Json<StopResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::EditItinerary).await?;
+
+    let stop = app_state.itinerary_service
+        .update_stop(room_id, stop_id, payload.into())
+        .await
+        .map_err(error_response)?;
+
+    broadcast_itinerary_updated(&app_state, room_id, user.id).await;
+
+    Ok(Json(StopResponse::from(stop)))
+}
+
+pub async fn delete_stop(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, stop_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::EditItinerary).await?;
+
+    app_state.itinerary_service
+        .delete_stop(room_id, stop_id)
+        .await
+        .map_err(error_response)?;
+
+    broadcast_itinerary_updated(&app_state, room_id, user.id).await;
+
+    Ok(Json(serde_json::json!({"message": "Stop deleted successfully"})))
+}
+
+pub async fn reorder_stops(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<ReorderStopsRequest>,
+) -> Result<Json<ItineraryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::EditItinerary).await?;
+
+    let stops = app_state.itinerary_service
+        .reorder_stops(room_id, payload.stop_ids)
+        .await
+        .map_err(error_response)?;
+
+    broadcast_itinerary_updated(&app_state, room_id, user.id).await;
+
+    Ok(Json(ItineraryResponse {
+        stops: stops.into_iter().map(StopResponse::from).collect(),
+    }))
+}
+
+pub async fn get_stop_events(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<StopEventsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let events = app_state.itinerary_service
+        .get_stop_events(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(StopEventsResponse {
+        events: events.into_iter().map(StopEventResponse::from).collect(),
+    }))
+}
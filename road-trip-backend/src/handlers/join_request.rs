@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::message::SystemEvent;
+use crate::entities::{join_request, user};
+use crate::handlers::access::{error_response, require_permission};
+use crate::handlers::message::post_system_message;
+use crate::handlers::room::member_name;
+use crate::services::permissions::Permission;
+use crate::services::websocket::WebSocketEvent;
+
+#[derive(Serialize)]
+pub struct JoinRequestResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub avatar: Option<String>,
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<(join_request::Model, user::Model)> for JoinRequestResponse {
+    fn from((request, user): (join_request::Model, user::Model)) -> Self {
+        Self {
+            id: request.id,
+            room_id: request.room_id,
+            user_id: request.user_id,
+            user_name: user.name,
+            avatar: user.avatar,
+            status: request.status,
+            created_at: request.created_at,
+            decided_by: request.decided_by,
+            decided_at: request.decided_at,
+        }
+    }
+}
+
+pub async fn get_join_requests(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<Vec<JoinRequestResponse>>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ApproveMembers).await?;
+
+    let requests = app_state.room_service
+        .get_join_requests(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(requests.into_iter().map(JoinRequestResponse::from).collect()))
+}
+
+pub async fn approve_join_request(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, request_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    decide(app_state, user, room_id, request_id, true).await
+}
+
+pub async fn deny_join_request(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, request_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    decide(app_state, user, room_id, request_id, false).await
+}
+
+async fn decide(
+    app_state: crate::routes::AppState,
+    user: user::Model,
+    room_id: Uuid,
+    request_id: Uuid,
+    approve: bool,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ApproveMembers).await?;
+
+    let request = app_state.room_service
+        .decide_join_request(room_id, request_id, user.id, approve)
+        .await
+        .map_err(error_response)?;
+
+    if approve {
+        let _ = app_state.websocket_service
+            .broadcast_to_room(room_id, WebSocketEvent::UserJoined { room_id, user_id: request.user_id })
+            .await;
+        let name = member_name(&app_state, request.user_id).await;
+        post_system_message(
+            &app_state,
+            room_id,
+            request.user_id,
+            format!("{} joined the room", name),
+            SystemEvent::MemberJoined { user_id: request.user_id },
+        )
+        .await;
+    }
+
+    let decided = WebSocketEvent::JoinRequestDecided {
+        room_id,
+        request_id: request.id,
+        user_id: request.user_id,
+        status: request.status.clone(),
+        decided_by: user.id,
+    };
+    let _ = app_state.websocket_service
+        .send_to_user(request.user_id, decided.clone())
+        .await;
+    notify_admins(&app_state, room_id, decided).await;
+
+    Ok(Json(serde_json::json!({
+        "id": request.id,
+        "room_id": room_id,
+        "user_id": request.user_id,
+        "status": request.status,
+    })))
+}
+
+/// Delivers an event to the room's admins and owner only
+pub(crate) async fn notify_admins(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    event: WebSocketEvent,
+) {
+    let admin_ids = match app_state.room_service.get_admin_ids(room_id).await {
+        Ok(admin_ids) => admin_ids,
+        Err(e) => {
+            tracing::warn!("Failed to load admins of room {}: {}", room_id, e);
+            return;
+        }
+    };
+
+    for admin_id in admin_ids {
+        let _ = app_state.websocket_service
+            .send_to_user(admin_id, event.clone())
+            .await;
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -7,7 +7,16 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::entities::user;
-use crate::entities::location;
+use crate::entities::{location, location_sharing};
+use crate::entities::message::SystemEvent;
+use crate::entities::stop_event::StopEventType;
+use crate::handlers::access::{error_response, require_member};
+use crate::handlers::message::post_system_message;
+use crate::services::location_service::{
+    BoundingBox, FixDistance, MemberDistance, MemberProgress, NextStopEstimate,
+};
+use crate::services::trail_service::{MemberTrack, TrailQuery};
+use crate::services::websocket::WebSocketEvent;
 
 #[derive(Deserialize)]
 pub struct UpdateLocationRequest {
@@ -25,11 +34,199 @@ pub struct LocationResponse {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateSharingSettingsRequest {
+    pub paused: bool,
+    /// Grid size in meters for reduced-precision sharing; `null` shares exact fixes
+    pub precision_meters: Option<f64>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct SharingSettingsResponse {
+    pub room_id: Uuid,
+    pub user_id: Uuid,
+    pub paused: bool,
+    pub precision_meters: Option<f64>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether the room currently sees this member's position
+    pub sharing: bool,
+}
+
 #[derive(Serialize)]
 pub struct LocationsResponse {
     pub locations: Vec<LocationResponse>,
 }
 
+#[derive(Deserialize)]
+pub struct LocationHistoryQuery {
+    pub user_id: Option<Uuid>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Douglas–Peucker tolerance in meters
+    pub tolerance_m: Option<f64>,
+    pub max_points: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct TrackResponse {
+    pub user_id: Uuid,
+    pub original_points: usize,
+    pub points: Vec<LocationResponse>,
+}
+
+#[derive(Serialize)]
+pub struct LocationHistoryResponse {
+    pub room_id: Uuid,
+    pub tracks: Vec<TrackResponse>,
+}
+
+impl From<LocationHistoryQuery> for TrailQuery {
+    fn from(query: LocationHistoryQuery) -> Self {
+        Self {
+            user_id: query.user_id,
+            since: query.since,
+            until: query.until,
+            tolerance_m: query.tolerance_m,
+            max_points: query.max_points,
+        }
+    }
+}
+
+impl From<MemberTrack> for TrackResponse {
+    fn from(track: MemberTrack) -> Self {
+        Self {
+            user_id: track.user_id,
+            original_points: track.original_points,
+            points: track.points.into_iter().map(LocationResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NearbyQuery {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_m: f64,
+}
+
+#[derive(Deserialize)]
+pub struct WithinQuery {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct FixDistanceResponse {
+    pub user_id: Uuid,
+    pub location: LocationResponse,
+    pub distance_meters: f64,
+}
+
+#[derive(Serialize)]
+pub struct NearbyResponse {
+    pub room_id: Uuid,
+    pub members: Vec<FixDistanceResponse>,
+}
+
+#[derive(Serialize)]
+pub struct StopDistancesResponse {
+    pub room_id: Uuid,
+    pub stop_id: Uuid,
+    pub members: Vec<FixDistanceResponse>,
+}
+
+impl From<FixDistance> for FixDistanceResponse {
+    fn from(d: FixDistance) -> Self {
+        Self {
+            user_id: d.location.user_id,
+            location: LocationResponse::from(d.location),
+            distance_meters: d.distance_meters,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MemberDistanceResponse {
+    pub user_a: Uuid,
+    pub user_b: Uuid,
+    pub distance_meters: f64,
+}
+
+#[derive(Serialize)]
+pub struct NextStopResponse {
+    pub stop_id: Uuid,
+    pub name: String,
+    pub distance_meters: f64,
+    pub eta_seconds: Option<i64>,
+    pub eta: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct MemberProgressResponse {
+    pub user_id: Uuid,
+    pub location: LocationResponse,
+    pub average_speed_mps: Option<f64>,
+    pub next_stop: Option<NextStopResponse>,
+}
+
+#[derive(Serialize)]
+pub struct DistancesResponse {
+    pub room_id: Uuid,
+    pub pairs: Vec<MemberDistanceResponse>,
+    pub members: Vec<MemberProgressResponse>,
+}
+
+impl From<MemberDistance> for MemberDistanceResponse {
+    fn from(d: MemberDistance) -> Self {
+        Self {
+            user_a: d.user_a,
+            user_b: d.user_b,
+            distance_meters: d.distance_meters,
+        }
+    }
+}
+
+impl From<NextStopEstimate> for NextStopResponse {
+    fn from(stop: NextStopEstimate) -> Self {
+        Self {
+            stop_id: stop.stop_id,
+            name: stop.name,
+            distance_meters: stop.distance_meters,
+            eta_seconds: stop.eta_seconds,
+            eta: stop.eta,
+        }
+    }
+}
+
+impl From<MemberProgress> for MemberProgressResponse {
+    fn from(progress: MemberProgress) -> Self {
+        Self {
+            user_id: progress.location.user_id,
+            location: LocationResponse::from(progress.location),
+            average_speed_mps: progress.average_speed_mps,
+            next_stop: progress.next_stop.map(NextStopResponse::from),
+        }
+    }
+}
+
+impl From<location_sharing::Model> for SharingSettingsResponse {
+    fn from(settings: location_sharing::Model) -> Self {
+        Self {
+            sharing: settings.is_sharing(chrono::Utc::now()),
+            room_id: settings.room_id,
+            user_id: settings.user_id,
+            paused: settings.paused,
+            precision_meters: settings.precision_meters,
+            expires_at: settings.expires_at,
+        }
+    }
+}
+
 impl From<location::Model> for LocationResponse {
     fn from(loc: location::Model) -> Self {
         Self {
@@ -56,6 +253,8 @@ pub async fn update_location(
             let error_msg = format!("{}", e);
             let status = if error_msg.contains("not a member") {
                 StatusCode::FORBIDDEN
+            } else if error_msg.contains("archived") {
+                StatusCode::CONFLICT
             } else {
                 StatusCode::INTERNAL_SERVER_ERROR
             };
@@ -65,9 +264,188 @@ pub async fn update_location(
             )
         })?;
 
+    // The room only hears about the fix as the member's sharing settings allow
+    let shared = app_state.location_service
+        .shared_fix(&location)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to load location sharing settings: {}", e);
+            None
+        });
+    if let Some(shared) = shared {
+        if shared == location {
+            announce_stop_events(&app_state, &user, &location).await;
+        }
+        let _ = app_state.websocket_service
+            .broadcast_to_room(
+                room_id,
+                WebSocketEvent::LocationUpdate {
+                    room_id,
+                    user_id: user.id,
+                    latitude: shared.latitude,
+                    longitude: shared.longitude,
+                },
+            )
+            .await;
+    }
+    announce_convoy_changes(&app_state, room_id).await;
+
     Ok(Json(LocationResponse::from(location)))
 }
 
+pub async fn get_location_history(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<LocationHistoryQuery>,
+) -> Result<Json<LocationHistoryResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let tracks = app_state.trail_service
+        .get_history(room_id, user.id, query.into())
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(LocationHistoryResponse {
+        room_id,
+        tracks: tracks.into_iter().map(TrackResponse::from).collect(),
+    }))
+}
+
+pub async fn get_distances(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<DistancesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let distances = app_state.location_service
+        .get_room_distances(room_id, user.id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(DistancesResponse {
+        room_id,
+        pairs: distances.pairs.into_iter().map(MemberDistanceResponse::from).collect(),
+        members: distances.members.into_iter().map(MemberProgressResponse::from).collect(),
+    }))
+}
+
+pub async fn get_nearby_members(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<NearbyQuery>,
+) -> Result<Json<NearbyResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let members = app_state.location_service
+        .get_nearby_members(room_id, user.id, query.latitude, query.longitude, query.radius_m)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(NearbyResponse {
+        room_id,
+        members: members.into_iter().map(FixDistanceResponse::from).collect(),
+    }))
+}
+
+pub async fn get_locations_within(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<WithinQuery>,
+) -> Result<Json<LocationsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let bbox = BoundingBox {
+        min_lat: query.min_lat,
+        min_lon: query.min_lon,
+        max_lat: query.max_lat,
+        max_lon: query.max_lon,
+    };
+    let locations = app_state.location_service
+        .get_fixes_in_bbox(room_id, user.id, bbox, query.since, query.until)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(LocationsResponse {
+        locations: locations.into_iter().map(LocationResponse::from).collect(),
+    }))
+}
+
+pub async fn get_stop_distances(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, stop_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<StopDistancesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let members = app_state.location_service
+        .get_stop_distances(room_id, stop_id, user.id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(StopDistancesResponse {
+        room_id,
+        stop_id,
+        members: members.into_iter().map(FixDistanceResponse::from).collect(),
+    }))
+}
+
+/// Posts a system chat message and a typed WebSocket event for every
+/// itinerary geofence the new fix crossed.
+async fn announce_stop_events(
+    app_state: &crate::routes::AppState,
+    user: &user::Model,
+    location: &location::Model,
+) {
+    let events = match app_state.location_service.detect_stop_events(location).await {
+        Ok(events) => events,
+        Err(e) => {
+            tracing::warn!("Failed to detect stop events: {}", e);
+            return;
+        }
+    };
+
+    for (event, stop) in events {
+        let arrived = event.event_type == StopEventType::Arrived.as_str();
+        let (text, system_event) = if arrived {
+            (
+                format!("{} arrived at {}", user.name, stop.name),
+                SystemEvent::ArrivedAtStop { user_id: user.id, stop_id: stop.id },
+            )
+        } else {
+            (
+                format!("{} left {}", user.name, stop.name),
+                SystemEvent::DepartedStop { user_id: user.id, stop_id: stop.id },
+            )
+        };
+        post_system_message(app_state, event.room_id, user.id, text, system_event).await;
+
+        let ws_event = if arrived {
+            WebSocketEvent::StopArrived {
+                room_id: event.room_id,
+                stop_id: event.stop_id,
+                user_id: event.user_id,
+                stop_name: stop.name,
+                occurred_at: event.occurred_at,
+            }
+        } else {
+            WebSocketEvent::StopDeparted {
+                room_id: event.room_id,
+                stop_id: event.stop_id,
+                user_id: event.user_id,
+                stop_name: stop.name,
+                occurred_at: event.occurred_at,
+            }
+        };
+        let _ = app_state.websocket_service
+            .broadcast_to_room(event.room_id, ws_event)
+            .await;
+    }
+}
+
 pub async fn get_locations(
     State(app_state): State<crate::routes::AppState>,
     Extension(user): Extension<user::Model>,
@@ -93,7 +471,7 @@ pub async fn get_locations(
     }
 
     let locations = app_state.location_service
-        .get_locations(room_id)
+        .get_locations(room_id, Some(user.id))
         .await
         .map_err(|e| {
             let error_msg = format!("{}", e);
@@ -112,3 +490,71 @@ pub async fn get_locations(
         locations: locations_response,
     }))
 }
+
+/// Broadcasts `convoy-separated` / `convoy-regrouped` for members whose
+/// distance from the convoy crossed the room's max spread.
+async fn announce_convoy_changes(app_state: &crate::routes::AppState, room_id: Uuid) {
+    let transitions = match app_state.location_service.update_convoy_state(room_id).await {
+        Ok(transitions) => transitions,
+        Err(e) => {
+            tracing::warn!("Failed to evaluate convoy spread: {}", e);
+            return;
+        }
+    };
+
+    for t in transitions {
+        let event = if t.separated {
+            WebSocketEvent::ConvoySeparated {
+                room_id,
+                user_id: t.user_id,
+                distance_meters: t.distance_meters,
+                max_spread_meters: t.max_spread_meters,
+            }
+        } else {
+            WebSocketEvent::ConvoyRegrouped {
+                room_id,
+                user_id: t.user_id,
+                distance_meters: t.distance_meters,
+                max_spread_meters: t.max_spread_meters,
+            }
+        };
+        let _ = app_state.websocket_service.broadcast_to_room(room_id, event).await;
+    }
+}
+
+pub async fn get_sharing_settings(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<SharingSettingsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let settings = app_state.location_service
+        .get_sharing_settings(room_id, user.id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(SharingSettingsResponse::from(settings)))
+}
+
+pub async fn update_sharing_settings(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<UpdateSharingSettingsRequest>,
+) -> Result<Json<SharingSettingsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let settings = app_state.location_service
+        .update_sharing_settings(
+            room_id,
+            user.id,
+            payload.paused,
+            payload.precision_meters,
+            payload.expires_at,
+        )
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(SharingSettingsResponse::from(settings)))
+}
@@ -4,21 +4,28 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::entities::user;
-use crate::entities::message;
+use crate::entities::message::{self, MessageType, SystemEvent};
+use crate::handlers::access::{
+    check_permission, error_response, require_member, require_permission, require_role,
+};
+use crate::services::message_service::{MessageDraft, ReactionCount, SearchCursor};
+use crate::services::permissions::Permission;
+use crate::services::poll_service::PollResults;
+use crate::services::websocket::WebSocketEvent;
 
 #[derive(Deserialize)]
 pub struct SendMessageRequest {
     pub text: Option<String>,
     pub image_url: Option<String>,
-    #[serde(default = "default_message_type")]
-    pub message_type: String,
-}
-
-fn default_message_type() -> String {
-    "text".to_string()
+    /// Inferred from `text` and `image_url` when omitted
+    pub message_type: Option<String>,
+    /// Required for `location` and `poll` messages
+    pub payload: Option<serde_json::Value>,
+    pub reply_to_id: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -37,6 +44,18 @@ fn default_page_size() -> u64 {
     20
 }
 
+#[derive(Deserialize)]
+pub struct SearchMessagesQuery {
+    pub q: String,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    #[serde(default = "default_page_size")]
+    pub limit: u64,
+}
+
+/// Largest page a search may return
+const MAX_SEARCH_LIMIT: u64 = 50;
+
 #[derive(Serialize)]
 pub struct MessageResponse {
     pub id: Uuid,
@@ -46,6 +65,87 @@ pub struct MessageResponse {
     pub image_url: Option<String>,
     pub message_type: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub reply_to_id: Option<Uuid>,
+    pub payload: Option<serde_json::Value>,
+    /// Quoted parent of a reply
+    pub reply_to: Option<ReplyPreview>,
+    pub reactions: Vec<ReactionCount>,
+    /// Members mentioned with `@name`
+    pub mentions: Vec<Uuid>,
+    /// Current results of a poll message
+    pub poll: Option<PollResults>,
+}
+
+#[derive(Serialize)]
+pub struct ReplyPreview {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// First characters of the parent's text
+    pub text: Option<String>,
+    pub message_type: String,
+}
+
+/// Length of the quoted parent text in a reply preview
+const REPLY_PREVIEW_CHARS: usize = 120;
+
+impl From<&message::Model> for ReplyPreview {
+    fn from(parent: &message::Model) -> Self {
+        Self {
+            id: parent.id,
+            user_id: parent.user_id,
+            text: parent.text.as_deref().map(|text| snippet(text, REPLY_PREVIEW_CHARS)),
+            message_type: parent.message_type.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RepliesResponse {
+    pub parent: MessageResponse,
+    pub replies: Vec<MessageResponse>,
+    pub total_pages: u64,
+    pub current_page: u64,
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    pub message: MessageResponse,
+    pub rank: f32,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>`
+    pub snippet: String,
+}
+
+#[derive(Serialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PinResponse {
+    pub message: MessageResponse,
+    pub pinned_by: Option<Uuid>,
+    pub pinned_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize)]
+pub struct PinsResponse {
+    pub pins: Vec<PinResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkReadRequest {
+    /// Defaults to the latest message in the room
+    pub message_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct MarkReadResponse {
+    pub room_id: Uuid,
+    pub last_read_message_id: Option<Uuid>,
+    pub last_read_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub unread_count: u64,
 }
 
 #[derive(Serialize)]
@@ -65,47 +165,133 @@ impl From<message::Model> for MessageResponse {
             image_url: msg.image_url,
             message_type: msg.message_type,
             created_at: msg.created_at,
+            reply_to_id: msg.reply_to_id,
+            payload: msg.payload,
+            reply_to: None,
+            reactions: Vec::new(),
+            mentions: Vec::new(),
+            poll: None,
         }
     }
 }
 
+/// Builds responses for a batch of messages as seen by `viewer_id`, quoting
+/// reply parents, counting reactions, listing mentions and tallying polls
+pub(crate) async fn message_responses(
+    app_state: &crate::routes::AppState,
+    viewer_id: Uuid,
+    messages: Vec<message::Model>,
+) -> anyhow::Result<Vec<MessageResponse>> {
+    let parent_ids: Vec<Uuid> = messages.iter().filter_map(|m| m.reply_to_id).collect();
+    let parents: HashMap<Uuid, message::Model> = app_state.message_service
+        .get_messages_by_ids(parent_ids)
+        .await?
+        .into_iter()
+        .map(|parent| (parent.id, parent))
+        .collect();
+    let message_ids: Vec<Uuid> = messages.iter().map(|m| m.id).collect();
+    let mut reactions = app_state.message_service
+        .get_reactions(message_ids.clone(), viewer_id)
+        .await?;
+    let mut mentions = app_state.message_service
+        .get_mentions(message_ids)
+        .await?;
+    let mut polls = app_state.poll_service
+        .get_results(&messages, viewer_id)
+        .await?;
+
+    Ok(messages
+        .into_iter()
+        .map(|msg| {
+            let reply_to = msg
+                .reply_to_id
+                .and_then(|id| parents.get(&id))
+                .map(ReplyPreview::from);
+            let reactions = reactions.remove(&msg.id).unwrap_or_default();
+            let mentions = mentions.remove(&msg.id).unwrap_or_default();
+            let poll = polls.remove(&msg.id);
+            MessageResponse {
+                reply_to,
+                reactions,
+                mentions,
+                poll,
+                ..MessageResponse::from(msg)
+            }
+        })
+        .collect())
+}
+
+/// Truncates on a character boundary, marking the cut with an ellipsis
+fn snippet(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
 pub async fn send_message(
     State(app_state): State<crate::routes::AppState>,
     Extension(user): Extension<user::Model>,
     Path(room_id): Path<Uuid>,
     Json(payload): Json<SendMessageRequest>,
-) -> Result<Json<MessageResponse>, StatusCode> {
-    // Verify membership
-    let is_member = app_state.message_service
-        .verify_membership(room_id, user.id)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<MessageResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
 
-    if !is_member {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    let message_type = match payload.message_type.as_deref() {
+        Some(value) => MessageType::parse(value)
+            .filter(|t| *t != MessageType::System)
+            .ok_or_else(|| {
+                error_response(anyhow::anyhow!(
+                    "Invalid message_type: expected text, image, location or poll"
+                ))
+            })?,
+        None if payload.text.is_none() && payload.image_url.is_some() => MessageType::Image,
+        None => MessageType::Text,
+    };
 
-    // Validate message type
-    let message_type = if payload.text.is_some() {
-        "text".to_string()
-    } else if payload.image_url.is_some() {
-        "image".to_string()
-    } else {
-        return Err(StatusCode::BAD_REQUEST);
+    let draft = MessageDraft {
+        message_type,
+        text: payload.text,
+        image_url: payload.image_url,
+        payload: payload.payload,
+        reply_to_id: payload.reply_to_id,
     };
+    let (message, mentions) = app_state.message_service
+        .send_message(room_id, user.id, draft)
+        .await
+        .map_err(error_response)?;
 
-    let message = app_state.message_service
-        .send_message(
-            room_id,
-            user.id,
-            payload.text,
-            payload.image_url,
-            message_type,
-        )
+    let _ = app_state.websocket_service
+        .broadcast_to_room(room_id, WebSocketEvent::new_message(&message, &mentions))
+        .await;
+
+    let mut responses = message_responses(&app_state, user.id, vec![message])
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(error_response)?;
 
-    Ok(Json(MessageResponse::from(message)))
+    Ok(Json(responses.remove(0)))
+}
+
+/// Posts a server-generated system message and broadcasts it to the room.
+/// Failures are logged rather than failing the action that triggered it.
+pub(crate) async fn post_system_message(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    user_id: Uuid,
+    text: String,
+    event: SystemEvent,
+) {
+    match app_state.message_service
+        .send_message(room_id, user_id, MessageDraft::system(text, event))
+        .await
+    {
+        Ok((message, mentions)) => {
+            let _ = app_state.websocket_service
+                .broadcast_to_room(room_id, WebSocketEvent::new_message(&message, &mentions))
+                .await;
+        }
+        Err(e) => tracing::warn!("Failed to post system message: {}", e),
+    }
 }
 
 pub async fn get_messages(
@@ -129,14 +315,383 @@ pub async fn get_messages(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let messages_response: Vec<MessageResponse> = messages
+    let messages_response = message_responses(&app_state, user.id, messages)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(MessagesResponse {
+        messages: messages_response,
+        total_pages,
+        current_page: query.page,
+    }))
+}
+
+pub async fn search_messages(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Query(query): Query<SearchMessagesQuery>,
+) -> Result<Json<SearchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(SearchCursor::parse)
+        .transpose()
+        .map_err(error_response)?;
+    let (hits, next_cursor) = app_state.message_service
+        .search(room_id, &query.q, cursor, query.limit.clamp(1, MAX_SEARCH_LIMIT))
+        .await
+        .map_err(error_response)?;
+
+    let ids = hits.iter().map(|hit| hit.id).collect();
+    let messages = app_state.message_service
+        .get_messages_by_ids(ids)
+        .await
+        .map_err(error_response)?;
+    let mut messages: HashMap<Uuid, MessageResponse> =
+        message_responses(&app_state, user.id, messages)
+            .await
+            .map_err(error_response)?
+            .into_iter()
+            .map(|message| (message.id, message))
+            .collect();
+
+    // Keep the ranked order; a message deleted in between is simply skipped
+    let results = hits
         .into_iter()
-        .map(MessageResponse::from)
+        .filter_map(|hit| {
+            messages.remove(&hit.id).map(|message| SearchResult {
+                message,
+                rank: hit.rank,
+                snippet: hit.snippet,
+            })
+        })
         .collect();
 
+    Ok(Json(SearchResponse {
+        results,
+        next_cursor: next_cursor.map(|cursor| cursor.encode()),
+    }))
+}
+
+/// Messages in any of the caller's rooms that mention them, newest first
+pub async fn get_my_mentions(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Query(query): Query<GetMessagesQuery>,
+) -> Result<Json<MessagesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let (messages, total_pages) = app_state.message_service
+        .get_user_mentions(user.id, query.page, query.page_size)
+        .await
+        .map_err(error_response)?;
+
+    let messages = message_responses(&app_state, user.id, messages)
+        .await
+        .map_err(error_response)?;
+
     Ok(Json(MessagesResponse {
-        messages: messages_response,
+        messages,
+        total_pages,
+        current_page: query.page,
+    }))
+}
+
+/// Authors may delete their own messages; deleting anyone else's needs the
+/// `DeleteOthersMessages` permission.
+pub async fn delete_message(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_role(&app_state, room_id, user.id).await?;
+
+    let message = app_state.message_service
+        .get_message(room_id, message_id)
+        .await
+        .map_err(error_response)?;
+    if message.user_id != user.id {
+        check_permission(role, Permission::DeleteOthersMessages)?;
+    }
+
+    let was_pinned = app_state.message_service
+        .delete_message(message.id)
+        .await
+        .map_err(error_response)?;
+
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            room_id,
+            WebSocketEvent::MessageDeleted {
+                room_id,
+                message_id: message.id,
+                deleted_by: user.id,
+            },
+        )
+        .await;
+    if was_pinned {
+        broadcast_pins_changed(&app_state, room_id, message.id, false, Some(user.id)).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_replies(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<GetMessagesQuery>,
+) -> Result<Json<RepliesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let parent = app_state.message_service
+        .get_message(room_id, message_id)
+        .await
+        .map_err(error_response)?;
+    let (replies, total_pages) = app_state.message_service
+        .get_replies(room_id, parent.id, query.page, query.page_size)
+        .await
+        .map_err(error_response)?;
+
+    let mut parent = message_responses(&app_state, user.id, vec![parent])
+        .await
+        .map_err(error_response)?;
+    let replies = message_responses(&app_state, user.id, replies)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(RepliesResponse {
+        parent: parent.remove(0),
+        replies,
         total_pages,
         current_page: query.page,
     }))
 }
+
+#[derive(Serialize)]
+pub struct ReactionsResponse {
+    pub message_id: Uuid,
+    pub reactions: Vec<ReactionCount>,
+}
+
+pub async fn add_reaction(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
+) -> Result<Json<ReactionsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let added = app_state.message_service
+        .add_reaction(room_id, message_id, user.id, &emoji)
+        .await
+        .map_err(error_response)?;
+
+    let response = reactions_response(&app_state, message_id, user.id).await?;
+    if added {
+        let count = reaction_count(&response, &emoji);
+        let _ = app_state.websocket_service
+            .broadcast_to_room(
+                room_id,
+                WebSocketEvent::ReactionAdded {
+                    room_id,
+                    message_id,
+                    user_id: user.id,
+                    emoji,
+                    count,
+                },
+            )
+            .await;
+    }
+
+    Ok(Json(response))
+}
+
+pub async fn remove_reaction(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
+) -> Result<Json<ReactionsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let removed = app_state.message_service
+        .remove_reaction(room_id, message_id, user.id, &emoji)
+        .await
+        .map_err(error_response)?;
+
+    let response = reactions_response(&app_state, message_id, user.id).await?;
+    if removed {
+        let count = reaction_count(&response, &emoji);
+        let _ = app_state.websocket_service
+            .broadcast_to_room(
+                room_id,
+                WebSocketEvent::ReactionRemoved {
+                    room_id,
+                    message_id,
+                    user_id: user.id,
+                    emoji,
+                    count,
+                },
+            )
+            .await;
+    }
+
+    Ok(Json(response))
+}
+
+async fn reactions_response(
+    app_state: &crate::routes::AppState,
+    message_id: Uuid,
+    viewer_id: Uuid,
+) -> Result<ReactionsResponse, (StatusCode, Json<serde_json::Value>)> {
+    let reactions = app_state.message_service
+        .get_reactions(vec![message_id], viewer_id)
+        .await
+        .map_err(error_response)?
+        .remove(&message_id)
+        .unwrap_or_default();
+
+    Ok(ReactionsResponse { message_id, reactions })
+}
+
+/// Members now reacting with `emoji`, after the change
+fn reaction_count(response: &ReactionsResponse, emoji: &str) -> u64 {
+    response
+        .reactions
+        .iter()
+        .find(|r| r.emoji == emoji)
+        .map_or(0, |r| r.count)
+}
+
+pub async fn get_pins(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<PinsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let (pins, messages): (Vec<_>, Vec<_>) = app_state.message_service
+        .get_pins(room_id)
+        .await
+        .map_err(error_response)?
+        .into_iter()
+        .unzip();
+    let messages = message_responses(&app_state, user.id, messages)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(PinsResponse {
+        pins: pins
+            .into_iter()
+            .zip(messages)
+            .map(|(pin, message)| PinResponse {
+                message,
+                pinned_by: pin.pinned_by,
+                pinned_at: pin.pinned_at,
+            })
+            .collect(),
+    }))
+}
+
+pub async fn pin_message(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::PinMessages).await?;
+
+    let pinned = app_state.message_service
+        .pin_message(room_id, message_id, user.id)
+        .await
+        .map_err(error_response)?;
+    if pinned {
+        broadcast_pins_changed(&app_state, room_id, message_id, true, Some(user.id)).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn unpin_message(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::PinMessages).await?;
+
+    let unpinned = app_state.message_service
+        .unpin_message(room_id, message_id)
+        .await
+        .map_err(error_response)?;
+    if unpinned {
+        broadcast_pins_changed(&app_state, room_id, message_id, false, Some(user.id)).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn broadcast_pins_changed(
+    app_state: &crate::routes::AppState,
+    room_id: Uuid,
+    message_id: Uuid,
+    pinned: bool,
+    changed_by: Option<Uuid>,
+) {
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            room_id,
+            WebSocketEvent::PinsChanged {
+                room_id,
+                message_id,
+                pinned,
+                changed_by,
+            },
+        )
+        .await;
+}
+
+pub async fn mark_read(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    payload: Option<Json<MarkReadRequest>>,
+) -> Result<Json<MarkReadResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let message_id = payload.and_then(|Json(payload)| payload.message_id);
+    let read = app_state.message_service
+        .mark_read(room_id, user.id, message_id)
+        .await
+        .map_err(error_response)?;
+
+    if let Some(message) = read {
+        let _ = app_state.websocket_service
+            .broadcast_to_room(
+                room_id,
+                WebSocketEvent::ReadReceipt {
+                    room_id,
+                    user_id: user.id,
+                    message_id: message.id,
+                    // Same value stored as the member's last_read_at
+                    read_at: message.created_at,
+                },
+            )
+            .await;
+    }
+
+    let member = app_state.room_service
+        .get_member(room_id, user.id)
+        .await
+        .map_err(error_response)?
+        .ok_or_else(|| error_response(anyhow::anyhow!("Member not found")))?;
+    let unread_count = app_state.message_service
+        .count_unread(room_id, user.id, member.read_cursor())
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(MarkReadResponse {
+        room_id,
+        last_read_message_id: member.last_read_message_id,
+        last_read_at: member.last_read_at,
+        unread_count,
+    }))
+}
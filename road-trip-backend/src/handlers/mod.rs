@@ -1,9 +1,26 @@
+pub mod access;
 pub mod auth;
 pub mod room;
 pub mod message;
+pub mod poll;
 pub mod location;
+pub mod itinerary;
+pub mod convoy;
+pub mod trip;
+pub mod sos;
+pub mod invite;
+pub mod join_request;
+pub mod voice_call;
 
 pub use auth::*;
 pub use room::*;
 pub use message::*;
-pub use location::*;
\ No newline at end of file
+pub use poll::*;
+pub use location::*;
+pub use itinerary::*;
+pub use convoy::*;
+pub use trip::*;
+pub use sos::*;
+pub use invite::*;
+pub use join_request::*;
+pub use voice_call::*;
\ No newline at end of file
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::entities::user;
+use crate::handlers::access::{error_response, require_member};
+use crate::services::poll_service::PollResults;
+use crate::services::websocket::WebSocketEvent;
+
+#[derive(Deserialize)]
+pub struct CastVoteRequest {
+    /// Indices of the chosen options; empty withdraws the vote
+    pub options: Vec<usize>,
+}
+
+pub async fn cast_vote(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<CastVoteRequest>,
+) -> Result<Json<PollResults>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let results = app_state.poll_service
+        .vote(room_id, message_id, user.id, payload.options)
+        .await
+        .map_err(error_response)?;
+
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            room_id,
+            WebSocketEvent::PollUpdated {
+                room_id,
+                message_id,
+                tallies: results.tallies.clone(),
+                total_voters: results.total_voters,
+                closed: results.closed,
+            },
+        )
+        .await;
+
+    Ok(Json(results))
+}
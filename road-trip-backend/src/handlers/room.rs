@@ -1,13 +1,24 @@
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     response::Response,
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::entities::user;
+use crate::entities::{room_ban, room_member, user};
+use crate::entities::room_member::RoomRole;
+use crate::handlers::access::{error_response, require_permission, require_role};
+use crate::handlers::join_request::notify_admins;
+use crate::handlers::location::LocationResponse;
+use crate::entities::message::SystemEvent;
+use crate::handlers::message::{message_responses, post_system_message, MessageResponse};
+use crate::handlers::voice_call::CallResponse;
+use crate::services::room_service::JoinOutcome;
+use crate::services::permissions::Permission;
+use crate::services::websocket::WebSocketEvent;
 use crate::utils::response;
 
 #[derive(Deserialize)]
@@ -16,9 +27,37 @@ pub struct CreateRoomRequest {
     pub description: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateRoomRequest {
+    pub name: Option<String>,
+    /// An empty string clears the description
+    pub description: Option<String>,
+    /// Require an admin to approve users joining with an invite code
+    pub approval_required: Option<bool>,
+    /// `null` falls back to the server-wide limit
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub max_members: Option<Option<i32>>,
+}
+
+/// Distinguishes an explicit `null` from a missing field
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+pub struct GetRoomsQuery {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
 #[derive(Deserialize)]
 pub struct JoinRoomRequest {
-    pub room_id: Uuid,
+    /// Invite code such as `RTB-7K2Q9M`
+    pub code: String,
 }
 
 #[derive(Serialize)]
@@ -30,15 +69,80 @@ pub struct RoomResponse {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub is_active: bool,
+    pub max_spread_meters: Option<f64>,
+    pub convoy_leader_id: Option<Uuid>,
+    pub approval_required: bool,
+    pub max_members: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: RoomRole,
+}
+
+#[derive(Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub user_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveMemberQuery {
+    /// Also bar the user from rejoining
+    #[serde(default)]
+    pub ban: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BanResponse {
+    pub user_id: Uuid,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub banned_by: Uuid,
+    pub reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Serialize)]
+pub struct RoomListItemResponse {
+    #[serde(flatten)]
+    pub room: RoomResponse,
+    pub unread_count: u64,
+}
+
+#[derive(Serialize)]
+pub struct RoomDetailResponse {
+    #[serde(flatten)]
+    pub room: RoomResponse,
+    /// The caller's role
+    pub role: RoomRole,
+    pub members: Vec<MemberDetailResponse>,
+    pub active_call: Option<CallResponse>,
+    pub last_message: Option<MessageResponse>,
+    pub unread_count: u64,
+}
+
+#[derive(Serialize)]
+pub struct MemberDetailResponse {
+    #[serde(flatten)]
+    pub member: MemberResponse,
+    /// Whether the member has a live WebSocket connection
+    pub online: bool,
+    /// Latest fix as the caller may see it; `null` when not shared
+    pub location: Option<LocationResponse>,
 }
 
 #[derive(Serialize)]
-pub struct UserResponse {
+pub struct MemberResponse {
     pub id: Uuid,
     pub name: String,
     pub email: String,
     pub avatar: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub role: RoomRole,
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    pub last_read_message_id: Option<Uuid>,
+    pub last_read_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl From<crate::entities::room::Model> for RoomResponse {
@@ -51,13 +155,34 @@ impl From<crate::entities::room::Model> for RoomResponse {
             created_at: room.created_at,
             updated_at: room.updated_at,
             is_active: room.is_active,
+            max_spread_meters: room.max_spread_meters,
+            convoy_leader_id: room.convoy_leader_id,
+            approval_required: room.approval_required,
+            max_members: room.max_members,
         }
     }
 }
 
-impl From<user::Model> for UserResponse {
-    fn from(user: user::Model) -> Self {
+impl From<(room_ban::Model, user::Model)> for BanResponse {
+    fn from((ban, user): (room_ban::Model, user::Model)) -> Self {
         Self {
+            user_id: user.id,
+            name: user.name,
+            avatar: user.avatar,
+            banned_by: ban.banned_by,
+            reason: ban.reason,
+            created_at: ban.created_at,
+        }
+    }
+}
+
+impl From<(room_member::Model, user::Model)> for MemberResponse {
+    fn from((member, user): (room_member::Model, user::Model)) -> Self {
+        Self {
+            role: member.room_role(),
+            joined_at: member.joined_at,
+            last_read_message_id: member.last_read_message_id,
+            last_read_at: member.last_read_at,
             id: user.id,
             name: user.name,
             email: user.email,
@@ -75,13 +200,7 @@ pub async fn create_room(
     let room = app_state.room_service
         .create_room(payload.name, payload.description, user.id)
         .await
-        .map_err(|e| {
-            let error_msg = format!("{}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": error_msg})),
-            )
-        })?;
+        .map_err(error_response)?;
 
     Ok(Json(RoomResponse::from(room)))
 }
@@ -89,21 +208,26 @@ pub async fn create_room(
 pub async fn get_rooms(
     State(app_state): State<crate::routes::AppState>,
     Extension(user): Extension<user::Model>,
-) -> Result<Json<Vec<RoomResponse>>, (StatusCode, Json<serde_json::Value>)> {
-    let rooms = app_state.room_service
-        .get_user_rooms(user.id)
-        .await
-        .map_err(|e| {
-            let error_msg = format!("{}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": error_msg})),
-            )
-        })?;
+    Query(query): Query<GetRoomsQuery>,
+) -> Result<Json<Vec<RoomListItemResponse>>, (StatusCode, Json<serde_json::Value>)> {
+    let (rooms, unread) = tokio::try_join!(
+        app_state.room_service.get_user_rooms(user.id, query.include_archived),
+        app_state.message_service.count_unread_by_room(user.id),
+    )
+    .map_err(|e| {
+        let error_msg = format!("{}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": error_msg})),
+        )
+    })?;
 
-    let rooms_response: Vec<RoomResponse> = rooms
+    let rooms_response: Vec<RoomListItemResponse> = rooms
         .into_iter()
-        .map(RoomResponse::from)
+        .map(|room| RoomListItemResponse {
+            unread_count: unread.get(&room.id).copied().unwrap_or(0),
+            room: RoomResponse::from(room),
+        })
         .collect();
 
     Ok(Json(rooms_response))
@@ -114,25 +238,60 @@ pub async fn join_room(
     Extension(user): Extension<user::Model>,
     Json(payload): Json<JoinRoomRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
-    app_state.room_service
-        .join_room(payload.room_id, user.id)
+    let outcome = app_state.room_service
+        .join_with_code(&payload.code, user.id)
         .await
-        .map_err(|e| {
-            let error_msg = format!("{}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({"error": error_msg})),
+        .map_err(error_response)?;
+
+    match outcome {
+        JoinOutcome::Joined(room) => {
+            let _ = app_state.websocket_service
+                .broadcast_to_room(room.id, WebSocketEvent::UserJoined { room_id: room.id, user_id: user.id })
+                .await;
+            post_system_message(
+                &app_state,
+                room.id,
+                user.id,
+                format!("{} joined the room", user.name),
+                SystemEvent::MemberJoined { user_id: user.id },
+            )
+            .await;
+
+            Ok(Json(serde_json::json!({
+                "message": "Joined room successfully",
+                "status": "joined",
+                "room": RoomResponse::from(room),
+            })))
+        }
+        JoinOutcome::Requested(room, request) => {
+            notify_admins(
+                &app_state,
+                room.id,
+                WebSocketEvent::JoinRequested {
+                    room_id: room.id,
+                    request_id: request.id,
+                    user_id: user.id,
+                    user_name: user.name.clone(),
+                    created_at: request.created_at,
+                },
             )
-        })?;
+            .await;
 
-    Ok(Json(serde_json::json!({"message": "Joined room successfully"})))
+            Ok(Json(serde_json::json!({
+                "message": "Join request sent; an admin needs to approve it",
+                "status": "pending",
+                "join_request_id": request.id,
+                "room": RoomResponse::from(room),
+            })))
+        }
+    }
 }
 
 pub async fn get_room_members(
     State(app_state): State<crate::routes::AppState>,
     Extension(user): Extension<user::Model>,
     Path(room_id): Path<Uuid>,
-) -> Result<Json<Vec<UserResponse>>, StatusCode> {
+) -> Result<Json<Vec<MemberResponse>>, StatusCode> {
     // Verify user is a member
     let is_member = app_state.room_service
         .is_member(room_id, user.id)
@@ -148,10 +307,330 @@ pub async fn get_room_members(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let members_response: Vec<UserResponse> = members
+    let members_response: Vec<MemberResponse> = members
         .into_iter()
-        .map(UserResponse::from)
+        .map(MemberResponse::from)
         .collect();
 
     Ok(Json(members_response))
 }
+
+pub async fn update_member_role(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, member_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ManageRoles).await?;
+
+    let member = app_state.room_service
+        .set_member_role(room_id, member_id, payload.role)
+        .await
+        .map_err(error_response)?;
+
+    broadcast_role_changed(&app_state, &member).await;
+
+    Ok(Json(serde_json::json!({
+        "room_id": member.room_id,
+        "user_id": member.user_id,
+        "role": member.room_role(),
+    })))
+}
+
+pub async fn transfer_ownership(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<TransferOwnershipRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::TransferOwnership).await?;
+
+    let members = app_state.room_service
+        .transfer_ownership(room_id, user.id, payload.user_id)
+        .await
+        .map_err(error_response)?;
+
+    for member in &members {
+        broadcast_role_changed(&app_state, member).await;
+    }
+
+    Ok(Json(serde_json::json!({
+        "room_id": room_id,
+        "owner_id": payload.user_id,
+    })))
+}
+
+/// Everything a room screen needs in one response
+pub async fn get_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<RoomDetailResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_role(&app_state, room_id, user.id).await?;
+
+    let (room, members, locations, active_call, last_message) = tokio::try_join!(
+        app_state.room_service.get_room_by_id(room_id),
+        app_state.room_service.get_room_members(room_id),
+        app_state.location_service.get_locations(room_id, Some(user.id)),
+        app_state.voice_call_service.get_active_call_in_room(room_id),
+        app_state.message_service.get_last_message(room_id),
+    )
+    .map_err(error_response)?;
+
+    let read_cursor = members
+        .iter()
+        .find(|(member, _)| member.user_id == user.id)
+        .map(|(member, _)| member.read_cursor())
+        .unwrap_or((room.created_at, None));
+    let unread_count = app_state.message_service
+        .count_unread(room_id, user.id, read_cursor)
+        .await
+        .map_err(error_response)?;
+
+    let last_message = match last_message {
+        Some(message) => message_responses(&app_state, user.id, vec![message])
+            .await
+            .map_err(error_response)?
+            .pop(),
+        None => None,
+    };
+
+    let member_ids: Vec<Uuid> = members.iter().map(|(member, _)| member.user_id).collect();
+    let online = app_state.websocket_service.online_users(&member_ids).await;
+    let mut locations: HashMap<Uuid, _> = locations
+        .into_iter()
+        .map(|location| (location.user_id, location))
+        .collect();
+
+    let members = members
+        .into_iter()
+        .map(|(member, member_user)| MemberDetailResponse {
+            online: online.contains(&member.user_id),
+            location: locations.remove(&member.user_id).map(LocationResponse::from),
+            member: MemberResponse::from((member, member_user)),
+        })
+        .collect();
+
+    Ok(Json(RoomDetailResponse {
+        room: RoomResponse::from(room),
+        role,
+        members,
+        active_call: active_call.map(CallResponse::from),
+        last_message,
+        unread_count,
+    }))
+}
+
+pub async fn update_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<UpdateRoomRequest>,
+) -> Result<Json<RoomResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::RenameRoom).await?;
+
+    let room = app_state.room_service
+        .update_room(
+            room_id,
+            payload.name,
+            payload.description,
+            payload.approval_required,
+            payload.max_members,
+        )
+        .await
+        .map_err(error_response)?;
+
+    broadcast_room_updated(&app_state, &room, user.id).await;
+
+    Ok(Json(RoomResponse::from(room)))
+}
+
+pub async fn archive_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<RoomResponse>, (StatusCode, Json<serde_json::Value>)> {
+    set_archived(app_state, user, room_id, true).await
+}
+
+pub async fn unarchive_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<RoomResponse>, (StatusCode, Json<serde_json::Value>)> {
+    set_archived(app_state, user, room_id, false).await
+}
+
+async fn set_archived(
+    app_state: crate::routes::AppState,
+    user: user::Model,
+    room_id: Uuid,
+    archived: bool,
+) -> Result<Json<RoomResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::ArchiveRoom).await?;
+
+    let room = app_state.room_service
+        .set_archived(room_id, archived)
+        .await
+        .map_err(error_response)?;
+
+    if archived {
+        app_state.location_service.forget_convoy_room(room_id).await;
+    }
+
+    broadcast_room_updated(&app_state, &room, user.id).await;
+
+    Ok(Json(RoomResponse::from(room)))
+}
+
+pub async fn delete_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::DeleteRoom).await?;
+
+    app_state.room_service
+        .delete_room(room_id)
+        .await
+        .map_err(error_response)?;
+
+    let _ = app_state.websocket_service
+        .broadcast_to_room(room_id, WebSocketEvent::RoomDeleted { room_id, deleted_by: user.id })
+        .await;
+    app_state.websocket_service.remove_room(room_id).await;
+    app_state.location_service.forget_convoy_room(room_id).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn leave_room(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    app_state.room_service
+        .leave_room(room_id, user.id)
+        .await
+        .map_err(error_response)?;
+
+    broadcast_member_removed(&app_state, room_id, user.id).await;
+    post_system_message(
+        &app_state,
+        room_id,
+        user.id,
+        format!("{} left the room", user.name),
+        SystemEvent::MemberLeft { user_id: user.id, removed_by: None },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn remove_member(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, member_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<RemoveMemberQuery>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_permission(&app_state, room_id, user.id, Permission::KickMembers).await?;
+
+    app_state.room_service
+        .kick_member(room_id, user.id, role, member_id, query.ban, query.reason)
+        .await
+        .map_err(error_response)?;
+
+    broadcast_member_removed(&app_state, room_id, member_id).await;
+    let name = member_name(&app_state, member_id).await;
+    post_system_message(
+        &app_state,
+        room_id,
+        member_id,
+        format!("{} was removed from the room", name),
+        SystemEvent::MemberLeft { user_id: member_id, removed_by: Some(user.id) },
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_bans(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<Vec<BanResponse>>, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::KickMembers).await?;
+
+    let bans = app_state.room_service
+        .get_bans(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(bans.into_iter().map(BanResponse::from).collect()))
+}
+
+pub async fn unban_member(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    require_permission(&app_state, room_id, user.id, Permission::KickMembers).await?;
+
+    app_state.room_service
+        .unban(room_id, member_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Display name for system messages about someone other than the caller
+pub(crate) async fn member_name(app_state: &crate::routes::AppState, user_id: Uuid) -> String {
+    app_state.auth_service
+        .get_user_by_id(user_id)
+        .await
+        .map(|user| user.name)
+        .unwrap_or_else(|_| "A member".to_string())
+}
+
+/// Tells the room the user is gone and cuts their sockets off from it
+async fn broadcast_member_removed(app_state: &crate::routes::AppState, room_id: Uuid, user_id: Uuid) {
+    let _ = app_state.websocket_service
+        .broadcast_to_room(room_id, WebSocketEvent::UserLeft { room_id, user_id })
+        .await;
+
+    app_state.websocket_service.unsubscribe_user(room_id, user_id).await;
+    app_state.location_service.forget_convoy_member(room_id, user_id).await;
+}
+
+async fn broadcast_room_updated(
+    app_state: &crate::routes::AppState,
+    room: &crate::entities::room::Model,
+    updated_by: Uuid,
+) {
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            room.id,
+            WebSocketEvent::RoomUpdated {
+                room_id: room.id,
+                name: room.name.clone(),
+                description: room.description.clone(),
+                is_active: room.is_active,
+                updated_by,
+            },
+        )
+        .await;
+}
+
+async fn broadcast_role_changed(app_state: &crate::routes::AppState, member: &room_member::Model) {
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            member.room_id,
+            WebSocketEvent::RoleChanged {
+                room_id: member.room_id,
+                user_id: member.user_id,
+                role: member.role.clone(),
+            },
+        )
+        .await;
+}
@@ -0,0 +1,130 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{sos_alert, user};
+use crate::handlers::access::{error_response, require_member};
+use crate::handlers::message::broadcast_pins_changed;
+use crate::services::websocket::WebSocketEvent;
+
+#[derive(Deserialize)]
+pub struct RaiseSosRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SosResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub user_id: Uuid,
+    pub note: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub located_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub message_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub acknowledged_by: Option<Uuid>,
+    pub acknowledged_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct SosListResponse {
+    pub alerts: Vec<SosResponse>,
+}
+
+impl From<sos_alert::Model> for SosResponse {
+    fn from(alert: sos_alert::Model) -> Self {
+        Self {
+            id: alert.id,
+            room_id: alert.room_id,
+            user_id: alert.user_id,
+            note: alert.note,
+            latitude: alert.latitude,
+            longitude: alert.longitude,
+            located_at: alert.located_at,
+            message_id: alert.message_id,
+            created_at: alert.created_at,
+            acknowledged_by: alert.acknowledged_by,
+            acknowledged_at: alert.acknowledged_at,
+        }
+    }
+}
+
+pub async fn raise_sos(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+    Json(payload): Json<RaiseSosRequest>,
+) -> Result<Json<SosResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let (alert, message) = app_state.sos_service
+        .raise_sos(room_id, user.id, &user.name, payload.note)
+        .await
+        .map_err(error_response)?;
+
+    let _ = app_state.websocket_service
+        .broadcast_to_room(room_id, WebSocketEvent::sos(&alert))
+        .await;
+    let _ = app_state.websocket_service
+        .broadcast_to_room(room_id, WebSocketEvent::new_message(&message, &[]))
+        .await;
+    broadcast_pins_changed(&app_state, room_id, message.id, true, None).await;
+
+    Ok(Json(SosResponse::from(alert)))
+}
+
+pub async fn get_active_sos(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<SosListResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let alerts = app_state.sos_service
+        .get_active_alerts(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(SosListResponse {
+        alerts: alerts.into_iter().map(SosResponse::from).collect(),
+    }))
+}
+
+pub async fn acknowledge_sos(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, sos_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<SosResponse>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let alert = app_state.sos_service
+        .acknowledge(room_id, sos_id, user.id)
+        .await
+        .map_err(error_response)?;
+
+    if let (Some(acknowledged_by), Some(acknowledged_at)) =
+        (alert.acknowledged_by, alert.acknowledged_at)
+    {
+        let _ = app_state.websocket_service
+            .broadcast_to_room(
+                room_id,
+                WebSocketEvent::SosAcknowledged {
+                    room_id,
+                    sos_id: alert.id,
+                    acknowledged_by,
+                    acknowledged_at,
+                },
+            )
+            .await;
+        if let Some(message_id) = alert.message_id {
+            broadcast_pins_changed(&app_state, room_id, message_id, false, None).await;
+        }
+    }
+
+    Ok(Json(SosResponse::from(alert)))
+}
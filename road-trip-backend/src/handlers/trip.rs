@@ -0,0 +1,25 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::entities::user;
+use crate::handlers::access::{error_response, require_member};
+use crate::services::trip_stats_service::TripSummary;
+
+pub async fn get_trip_summary(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path(room_id): Path<Uuid>,
+) -> Result<Json<TripSummary>, (StatusCode, Json<serde_json::Value>)> {
+    require_member(&app_state, room_id, user.id).await?;
+
+    let summary = app_state.trip_stats_service
+        .get_summary(room_id)
+        .await
+        .map_err(error_response)?;
+
+    Ok(Json(summary))
+}
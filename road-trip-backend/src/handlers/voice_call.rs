@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{user, voice_call};
+use crate::handlers::access::{check_permission, error_response, require_role};
+use crate::services::permissions::Permission;
+use crate::services::websocket::WebSocketEvent;
+
+#[derive(Serialize)]
+pub struct CallResponse {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub initiator_id: Uuid,
+    pub status: String,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<voice_call::Model> for CallResponse {
+    fn from(call: voice_call::Model) -> Self {
+        Self {
+            id: call.id,
+            room_id: call.room_id,
+            initiator_id: call.initiator_id,
+            status: call.status,
+            start_time: call.start_time,
+        }
+    }
+}
+
+/// The caller who started a call may end it; ending anyone else's call
+/// needs the `EndCalls` permission.
+pub async fn end_call(
+    State(app_state): State<crate::routes::AppState>,
+    Extension(user): Extension<user::Model>,
+    Path((room_id, call_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let role = require_role(&app_state, room_id, user.id).await?;
+
+    let call = app_state.voice_call_service
+        .get_room_call(room_id, call_id)
+        .await
+        .map_err(error_response)?;
+    if call.initiator_id != user.id {
+        check_permission(role, Permission::EndCalls)?;
+    }
+
+    app_state.voice_call_service
+        .end_call(call.id)
+        .await
+        .map_err(error_response)?;
+
+    let _ = app_state.websocket_service
+        .broadcast_to_room(
+            room_id,
+            WebSocketEvent::VoiceCallEnded {
+                room_id,
+                call_id: call.id,
+                ended_by: user.id,
+            },
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,42 @@
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+
+use crate::config::LocationConfig;
+use crate::services::RetentionService;
+
+/// Periodically purges raw location history of rooms archived longer than
+/// the retention period
+pub fn spawn(db: DatabaseConnection, config: LocationConfig) {
+    let period = Duration::from_secs(config.retention_interval_minutes.max(1) * 60);
+
+    tokio::spawn(async move {
+        let dry_run = config.retention_dry_run;
+        let retention_service = RetentionService::new(db, config);
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match retention_service.purge_archived_locations().await {
+                Ok(reports) => {
+                    for report in reports {
+                        if dry_run {
+                            tracing::info!(
+                                "Location retention (dry run) would purge {} fixes from room {}",
+                                report.rows,
+                                report.room_id
+                            );
+                        } else {
+                            tracing::info!(
+                                "Location retention purged {} fixes from room {}",
+                                report.rows,
+                                report.room_id
+                            );
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("Location retention failed: {}", e),
+            }
+        }
+    });
+}
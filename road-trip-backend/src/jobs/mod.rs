@@ -0,0 +1,3 @@
+pub mod trail_compaction;
+pub mod location_retention;
+pub mod sos_rebroadcast;
@@ -0,0 +1,38 @@
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::services::websocket::{WebSocketEvent, WebSocketService};
+use crate::services::SosService;
+
+/// How often unacknowledged SOS alerts are re-sent to their room
+const SOS_REBROADCAST_SECS: u64 = 15;
+
+/// Keeps re-broadcasting every unacknowledged SOS alert so members who
+/// connect late, or missed the first event, still see it
+pub fn spawn(db: DatabaseConnection, websocket_service: Arc<WebSocketService>) {
+    tokio::spawn(async move {
+        let sos_service = SosService::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(SOS_REBROADCAST_SECS));
+        // The first tick fires immediately; alerts were just broadcast when raised
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let alerts = match sos_service.get_all_active_alerts().await {
+                Ok(alerts) => alerts,
+                Err(e) => {
+                    tracing::error!("Failed to load active SOS alerts: {}", e);
+                    continue;
+                }
+            };
+
+            for alert in alerts {
+                let _ = websocket_service
+                    .broadcast_to_room(alert.room_id, WebSocketEvent::sos(&alert))
+                    .await;
+            }
+        }
+    });
+}
@@ -0,0 +1,25 @@
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+
+use crate::config::LocationConfig;
+use crate::services::TrailService;
+
+/// Periodically decimates location history older than the configured age
+pub fn spawn(db: DatabaseConnection, config: LocationConfig) {
+    let period = Duration::from_secs(config.trail_compaction_interval_minutes.max(1) * 60);
+
+    tokio::spawn(async move {
+        let trail_service = TrailService::new(db, config);
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match trail_service.compact_old_trails().await {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("Trail compaction removed {} location fixes", removed),
+                Err(e) => tracing::error!("Trail compaction failed: {}", e),
+            }
+        }
+    });
+}
@@ -6,6 +6,7 @@ use tracing_subscriber;
 mod config;
 mod entities;
 mod handlers;
+mod jobs;
 mod middleware;
 mod routes;
 mod services;
@@ -15,6 +16,7 @@ use config::Config;
 use config::database::create_connection;
 use middleware::cors::create_cors_layer;
 use routes::create_router;
+use services::websocket::WebSocketService;
 use sea_orm::{ConnectionTrait, Statement};
 
 #[tokio::main]
@@ -55,8 +57,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     })?;
     tracing::info!("Database migrations completed");
 
+    // Shared by the router and jobs that push events to connected clients
+    let websocket_service = Arc::new(WebSocketService::new());
+
+    // Start background jobs
+    jobs::trail_compaction::spawn((*db).clone(), config.location.clone());
+    jobs::location_retention::spawn((*db).clone(), config.location.clone());
+    jobs::sos_rebroadcast::spawn((*db).clone(), websocket_service.clone());
+
     // Create router
-    let app = create_router(db, config.clone())
+    let app = create_router(db, config.clone(), websocket_service)
         .layer(create_cors_layer(&config))
         .layer(TraceLayer::new_for_http());
 
@@ -152,6 +162,45 @@ async fn run_migrations_seaorm(db: &sea_orm::DatabaseConnection) -> Result<(), B
     );
     let _ = db.execute(stmt).await; // Ignore error if column already exists
     
+    // Convoy settings (added after initial schema)
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS max_spread_meters DOUBLE PRECISION"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS convoy_leader_id UUID REFERENCES users(id) ON DELETE SET NULL"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS approval_required BOOLEAN NOT NULL DEFAULT false"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS max_members INTEGER"
+    );
+    db.execute(stmt).await?;
+    
+    // Archive time for retention; rooms archived before the column existed
+    // fall back to their last update
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "UPDATE rooms SET archived_at = updated_at WHERE is_active = false AND archived_at IS NULL"
+    );
+    db.execute(stmt).await?;
+    
     let stmt = Statement::from_string(
         db.get_database_backend(),
         "CREATE INDEX IF NOT EXISTS idx_rooms_created_by ON rooms(created_by)"
@@ -173,6 +222,27 @@ async fn run_migrations_seaorm(db: &sea_orm::DatabaseConnection) -> Result<(), B
     );
     db.execute(stmt).await?;
     
+    // Add role column; existing rooms without an owner get their creator as owner
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE room_members ADD COLUMN IF NOT EXISTS role VARCHAR(16) NOT NULL DEFAULT 'member'"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        UPDATE room_members rm SET role = 'owner'
+        FROM rooms r
+        WHERE rm.room_id = r.id
+            AND rm.user_id = r.created_by
+            AND NOT EXISTS (
+                SELECT 1 FROM room_members o WHERE o.room_id = r.id AND o.role = 'owner'
+            )
+        "#
+    );
+    db.execute(stmt).await?;
+    
     let stmt = Statement::from_string(
         db.get_database_backend(),
         "CREATE INDEX IF NOT EXISTS idx_room_members_room_id ON room_members(room_id)"
@@ -220,6 +290,46 @@ async fn run_migrations_seaorm(db: &sea_orm::DatabaseConnection) -> Result<(), B
     );
     db.execute(stmt).await?;
     
+    // Replies (added after initial schema)
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE messages ADD COLUMN IF NOT EXISTS reply_to_id UUID REFERENCES messages(id) ON DELETE SET NULL"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_messages_reply_to_id ON messages(reply_to_id)"
+    );
+    db.execute(stmt).await?;
+    
+    // Structured message payloads (added after initial schema)
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE messages ADD COLUMN IF NOT EXISTS payload JSONB"
+    );
+    db.execute(stmt).await?;
+    
+    // Full-text search; the expression must match the one in MessageService::search
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_messages_text_search ON messages USING GIN (to_tsvector('english', COALESCE(text, '')))"
+    );
+    db.execute(stmt).await?;
+    
+    // Read receipts (added after initial schema; needs the messages table)
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE room_members ADD COLUMN IF NOT EXISTS last_read_message_id UUID REFERENCES messages(id) ON DELETE SET NULL"
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "ALTER TABLE room_members ADD COLUMN IF NOT EXISTS last_read_at TIMESTAMPTZ"
+    );
+    db.execute(stmt).await?;
+    
     // Create locations table
     let stmt = Statement::from_string(
         db.get_database_backend(),
@@ -262,6 +372,68 @@ async fn run_migrations_seaorm(db: &sea_orm::DatabaseConnection) -> Result<(), B
     );
     db.execute(stmt).await?;
     
+    // Serves latest-fix-per-member and recent trail lookups
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_locations_room_user_timestamp ON locations(room_id, user_id, timestamp DESC)"
+    );
+    db.execute(stmt).await?;
+    
+    // PostGIS is optional: when the extension can be enabled, locations get an
+    // indexed geography column kept in sync by a trigger; otherwise spatial
+    // queries fall back to haversine in Rust
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE EXTENSION IF NOT EXISTS postgis"
+    );
+    if let Err(e) = db.execute(stmt).await {
+        tracing::info!("PostGIS not available, using haversine for spatial queries: {}", e);
+    } else {
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            "ALTER TABLE locations ADD COLUMN IF NOT EXISTS geog geography(Point, 4326)"
+        );
+        db.execute(stmt).await?;
+        
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            r#"
+            CREATE OR REPLACE FUNCTION locations_set_geog() RETURNS trigger AS $$
+            BEGIN
+                NEW.geog := ST_SetSRID(ST_MakePoint(NEW.longitude, NEW.latitude), 4326)::geography;
+                RETURN NEW;
+            END;
+            $$ LANGUAGE plpgsql
+            "#
+        );
+        db.execute(stmt).await?;
+        
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            "DROP TRIGGER IF EXISTS trg_locations_set_geog ON locations"
+        );
+        db.execute(stmt).await?;
+        
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            "CREATE TRIGGER trg_locations_set_geog BEFORE INSERT OR UPDATE OF latitude, longitude ON locations FOR EACH ROW EXECUTE FUNCTION locations_set_geog()"
+        );
+        db.execute(stmt).await?;
+        
+        // Backfill fixes recorded before PostGIS was enabled
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            "UPDATE locations SET geog = ST_SetSRID(ST_MakePoint(longitude, latitude), 4326)::geography WHERE geog IS NULL"
+        );
+        db.execute(stmt).await?;
+        
+        let stmt = Statement::from_string(
+            db.get_database_backend(),
+            "CREATE INDEX IF NOT EXISTS idx_locations_geog ON locations USING GIST (geog)"
+        );
+        db.execute(stmt).await?;
+    }
+    
     // Create voice_calls table
     let stmt = Statement::from_string(
         db.get_database_backend(),
@@ -297,6 +469,297 @@ async fn run_migrations_seaorm(db: &sea_orm::DatabaseConnection) -> Result<(), B
     );
     db.execute(stmt).await?;
     
+    // Create itinerary_stops table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS itinerary_stops (
+            id UUID PRIMARY KEY,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            name VARCHAR NOT NULL,
+            latitude DOUBLE PRECISION NOT NULL,
+            longitude DOUBLE PRECISION NOT NULL,
+            timezone VARCHAR NOT NULL DEFAULT 'UTC',
+            planned_arrival TIMESTAMPTZ,
+            planned_departure TIMESTAMPTZ,
+            notes VARCHAR,
+            created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_itinerary_stops_room_position ON itinerary_stops(room_id, position)"
+    );
+    db.execute(stmt).await?;
+    
+    // Positions are unique per room; deferred so reorders can swap them.
+    // Duplicates left by older servers are renumbered first.
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        DO $$
+        BEGIN
+            IF NOT EXISTS (
+                SELECT 1 FROM pg_constraint WHERE conname = 'uq_itinerary_stops_room_position'
+            ) THEN
+                UPDATE itinerary_stops s
+                SET position = r.position
+                FROM (
+                    SELECT id, ROW_NUMBER() OVER (PARTITION BY room_id ORDER BY position, created_at) - 1 AS position
+                    FROM itinerary_stops
+                ) r
+                WHERE s.id = r.id AND s.position <> r.position;
+
+                ALTER TABLE itinerary_stops
+                    ADD CONSTRAINT uq_itinerary_stops_room_position UNIQUE (room_id, position)
+                    DEFERRABLE INITIALLY DEFERRED;
+            END IF;
+        END
+        $$
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create stop_events table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS stop_events (
+            id UUID PRIMARY KEY,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            stop_id UUID NOT NULL REFERENCES itinerary_stops(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            event_type VARCHAR NOT NULL,
+            latitude DOUBLE PRECISION NOT NULL,
+            longitude DOUBLE PRECISION NOT NULL,
+            distance_meters DOUBLE PRECISION NOT NULL,
+            occurred_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_stop_events_room_user ON stop_events(room_id, user_id, occurred_at)"
+    );
+    db.execute(stmt).await?;
+    
+    // Create trip_summaries table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS trip_summaries (
+            room_id UUID PRIMARY KEY REFERENCES rooms(id) ON DELETE CASCADE,
+            summary JSONB NOT NULL,
+            computed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create trail_compactions table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS trail_compactions (
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            compacted_until TIMESTAMPTZ NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (room_id, user_id)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create location_sharing table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS location_sharing (
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            paused BOOLEAN NOT NULL DEFAULT FALSE,
+            precision_meters DOUBLE PRECISION,
+            expires_at TIMESTAMPTZ,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (room_id, user_id)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create message_pins table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS message_pins (
+            message_id UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            pinned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+            pinned_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_message_pins_room_id ON message_pins(room_id, pinned_at)"
+    );
+    db.execute(stmt).await?;
+    
+    // Create sos_alerts table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS sos_alerts (
+            id UUID PRIMARY KEY,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            note TEXT,
+            latitude DOUBLE PRECISION,
+            longitude DOUBLE PRECISION,
+            located_at TIMESTAMPTZ,
+            message_id UUID REFERENCES messages(id) ON DELETE SET NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            acknowledged_by UUID REFERENCES users(id) ON DELETE SET NULL,
+            acknowledged_at TIMESTAMPTZ
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_sos_alerts_active ON sos_alerts(room_id) WHERE acknowledged_at IS NULL"
+    );
+    db.execute(stmt).await?;
+    
+    // Create room_invites table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS room_invites (
+            id UUID PRIMARY KEY,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            code VARCHAR(32) NOT NULL UNIQUE,
+            created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            expires_at TIMESTAMPTZ,
+            max_uses INTEGER,
+            uses INTEGER NOT NULL DEFAULT 0,
+            revoked_at TIMESTAMPTZ
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_room_invites_room_id ON room_invites(room_id)"
+    );
+    db.execute(stmt).await?;
+    
+    // Create room_bans table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS room_bans (
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            banned_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            reason TEXT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (room_id, user_id)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create join_requests table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS join_requests (
+            id UUID PRIMARY KEY,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            invite_id UUID REFERENCES room_invites(id) ON DELETE SET NULL,
+            status VARCHAR(16) NOT NULL DEFAULT 'pending',
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+            decided_at TIMESTAMPTZ
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // At most one pending request per user and room
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_join_requests_pending ON join_requests(room_id, user_id) WHERE status = 'pending'"
+    );
+    db.execute(stmt).await?;
+    
+    // Create message_reactions table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS message_reactions (
+            message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            emoji VARCHAR(32) NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (message_id, user_id, emoji)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create poll_votes table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS poll_votes (
+            message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            option_index INTEGER NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (message_id, user_id, option_index)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    // Create message_mentions table
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS message_mentions (
+            message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (message_id, user_id)
+        )
+        "#
+    );
+    db.execute(stmt).await?;
+    
+    let stmt = Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_message_mentions_user_id ON message_mentions(user_id, created_at)"
+    );
+    db.execute(stmt).await?;
+    
     tracing::info!("All database tables created successfully");
     
     Ok(())
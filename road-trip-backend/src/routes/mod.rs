@@ -1,17 +1,35 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
 
 use crate::config::Config;
 use crate::handlers::auth::{get_current_user, login, logout, register};
-use crate::handlers::room::{create_room, get_rooms, join_room, get_room_members};
-use crate::handlers::message::{send_message, get_messages};
-use crate::handlers::location::{update_location, get_locations};
+use crate::handlers::room::{
+    create_room, get_rooms, join_room, get_room_members, update_member_role, transfer_ownership,
+    leave_room, remove_member, get_bans, unban_member, update_room, archive_room, unarchive_room,
+    delete_room, get_room,
+};
+use crate::handlers::message::{
+    send_message, get_messages, delete_message, mark_read, get_replies, add_reaction,
+    remove_reaction, search_messages, get_pins, pin_message, unpin_message, get_my_mentions,
+};
+use crate::handlers::poll::cast_vote;
+use crate::handlers::voice_call::end_call;
+use crate::handlers::location::{
+    update_location, get_locations, get_location_history, get_distances, get_sharing_settings,
+    update_sharing_settings, get_nearby_members, get_locations_within, get_stop_distances,
+};
+use crate::handlers::convoy::{get_convoy_status, update_convoy_settings};
+use crate::handlers::trip::get_trip_summary;
+use crate::handlers::sos::{raise_sos, get_active_sos, acknowledge_sos};
+use crate::handlers::invite::{create_invite, get_invites, revoke_invite};
+use crate::handlers::join_request::{get_join_requests, approve_join_request, deny_join_request};
+use crate::handlers::itinerary::{get_itinerary, create_stop, update_stop, delete_stop, reorder_stops, get_stop_events};
 use crate::middleware::auth::auth_middleware;
-use crate::services::{AuthService, RoomService, MessageService, LocationService};
+use crate::services::{AuthService, RoomService, MessageService, LocationService, ItineraryService, TripStatsService, TrailService, SosService, PollService, VoiceCallSignalingService};
 use crate::services::websocket::{WebSocketService, websocket_handler};
 use sea_orm::DatabaseConnection;
 
@@ -21,19 +39,32 @@ pub struct AppState {
     pub room_service: Arc<RoomService>,
     pub message_service: Arc<MessageService>,
     pub location_service: Arc<LocationService>,
+    pub itinerary_service: Arc<ItineraryService>,
+    pub trip_stats_service: Arc<TripStatsService>,
+    pub trail_service: Arc<TrailService>,
+    pub sos_service: Arc<SosService>,
+    pub poll_service: Arc<PollService>,
+    pub voice_call_service: Arc<VoiceCallSignalingService>,
     pub websocket_service: Arc<WebSocketService>,
 }
 
 pub fn create_router(
     db: Arc<DatabaseConnection>,
     config: Arc<Config>,
+    websocket_service: Arc<WebSocketService>,
 ) -> Router<()> {
     let app_state = AppState {
         auth_service: Arc::new(AuthService::new((*db).clone())),
-        room_service: Arc::new(RoomService::new((*db).clone())),
+        room_service: Arc::new(RoomService::new((*db).clone(), config.rooms.clone())),
         message_service: Arc::new(MessageService::new((*db).clone())),
-        location_service: Arc::new(LocationService::new((*db).clone())),
-        websocket_service: Arc::new(WebSocketService::new()),
+        location_service: Arc::new(LocationService::new((*db).clone(), config.location.clone())),
+        itinerary_service: Arc::new(ItineraryService::new((*db).clone())),
+        trip_stats_service: Arc::new(TripStatsService::new((*db).clone())),
+        trail_service: Arc::new(TrailService::new((*db).clone(), config.location.clone())),
+        sos_service: Arc::new(SosService::new((*db).clone())),
+        poll_service: Arc::new(PollService::new((*db).clone())),
+        voice_call_service: Arc::new(VoiceCallSignalingService::new((*db).clone())),
+        websocket_service,
     };
 
     let auth_layer = middleware::from_fn(auth_middleware);
@@ -61,26 +92,186 @@ pub fn create_router(
             "/api/rooms/join",
             post(join_room).layer(auth_layer.clone()),
         )
+        .route(
+            "/api/rooms/{room_id}",
+            get(get_room).patch(update_room).delete(delete_room).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/archive",
+            post(archive_room).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/unarchive",
+            post(unarchive_room).layer(auth_layer.clone()),
+        )
         .route(
             "/api/rooms/{room_id}/members",
             get(get_room_members).layer(auth_layer.clone()),
         )
+        .route(
+            "/api/rooms/{room_id}/members/{user_id}",
+            delete(remove_member).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/leave",
+            post(leave_room).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/bans",
+            get(get_bans).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/bans/{user_id}",
+            delete(unban_member).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/members/{user_id}/role",
+            put(update_member_role).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/transfer-ownership",
+            post(transfer_ownership).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/invites",
+            get(get_invites).post(create_invite).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/invites/{invite_id}",
+            delete(revoke_invite).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/join-requests",
+            get(get_join_requests).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/join-requests/{request_id}/approve",
+            post(approve_join_request).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/join-requests/{request_id}/deny",
+            post(deny_join_request).layer(auth_layer.clone()),
+        )
         // Protected message routes
+        .route(
+            "/api/me/mentions",
+            get(get_my_mentions).layer(auth_layer.clone()),
+        )
         .route(
             "/api/rooms/{room_id}/messages",
             get(get_messages).post(send_message).layer(auth_layer.clone()),
         )
+        .route(
+            "/api/rooms/{room_id}/messages/search",
+            get(search_messages).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/messages/{message_id}",
+            delete(delete_message).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/messages/{message_id}/replies",
+            get(get_replies).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/messages/{message_id}/reactions/{emoji}",
+            put(add_reaction).delete(remove_reaction).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/polls/{message_id}/votes",
+            post(cast_vote).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/pins",
+            get(get_pins).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/pins/{message_id}",
+            post(pin_message).delete(unpin_message).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/read",
+            post(mark_read).layer(auth_layer.clone()),
+        )
+        // Protected voice call routes
+        .route(
+            "/api/rooms/{room_id}/calls/{call_id}/end",
+            post(end_call).layer(auth_layer.clone()),
+        )
         // Protected location routes
         .route(
             "/api/rooms/{room_id}/location",
             post(update_location).layer(auth_layer.clone()),
         )
+        .route(
+            "/api/rooms/{room_id}/location/sharing",
+            get(get_sharing_settings)
+                .put(update_sharing_settings)
+                .layer(auth_layer.clone()),
+        )
         .route(
             "/api/rooms/{room_id}/locations",
             get(get_locations).layer(auth_layer.clone()),
         )
+        .route(
+            "/api/rooms/{room_id}/locations/history",
+            get(get_location_history).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/locations/nearby",
+            get(get_nearby_members).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/locations/within",
+            get(get_locations_within).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/distances",
+            get(get_distances).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/summary",
+            get(get_trip_summary).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/convoy",
+            get(get_convoy_status).put(update_convoy_settings).layer(auth_layer.clone()),
+        )
+        // Protected SOS routes
+        .route(
+            "/api/rooms/{room_id}/sos",
+            get(get_active_sos).post(raise_sos).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/sos/{sos_id}/ack",
+            post(acknowledge_sos).layer(auth_layer.clone()),
+        )
+        // Protected itinerary routes
+        .route(
+            "/api/rooms/{room_id}/itinerary",
+            get(get_itinerary).post(create_stop).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/itinerary/reorder",
+            post(reorder_stops).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/itinerary/events",
+            get(get_stop_events).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/itinerary/{stop_id}",
+            put(update_stop).delete(delete_stop).layer(auth_layer.clone()),
+        )
+        .route(
+            "/api/rooms/{room_id}/itinerary/{stop_id}/distances",
+            get(get_stop_distances).layer(auth_layer.clone()),
+        )
         // WebSocket route
-        .route("/ws", axum::routing::get(websocket_handler))
+        .route(
+            "/ws",
+            axum::routing::get(websocket_handler).layer(auth_layer.clone()),
+        )
         .with_state(app_state)
         .layer(axum::Extension(config))
         .layer(axum::Extension(db))
@@ -0,0 +1,273 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{itinerary_stop, room, stop_event};
+use crate::services::room_service::ensure_room_active;
+
+/// Fields of a stop as submitted by the client. Planned times are wall-clock
+/// times at the stop, interpreted in `timezone`.
+pub struct StopInput {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: String,
+    pub planned_arrival: Option<NaiveDateTime>,
+    pub planned_departure: Option<NaiveDateTime>,
+    pub notes: Option<String>,
+}
+
+pub struct ItineraryService {
+    db: DatabaseConnection,
+}
+
+impl ItineraryService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_stops(&self, room_id: Uuid) -> Result<Vec<itinerary_stop::Model>> {
+        let stops = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .order_by_asc(itinerary_stop::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        Ok(stops)
+    }
+
+    pub async fn create_stop(
+        &self,
+        room_id: Uuid,
+        created_by: Uuid,
+        input: StopInput,
+    ) -> Result<itinerary_stop::Model> {
+        let (tz, arrival, departure) = validate_stop(&input)?;
+
+        let txn = self.db.begin().await?;
+        lock_room(&txn, room_id).await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        // New stops are appended to the end of the itinerary
+        let position = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .order_by_desc(itinerary_stop::Column::Position)
+            .one(&txn)
+            .await?
+            .map_or(0, |last| last.position + 1);
+
+        let new_stop = itinerary_stop::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            room_id: Set(room_id),
+            position: Set(position),
+            name: Set(input.name.trim().to_string()),
+            latitude: Set(input.latitude),
+            longitude: Set(input.longitude),
+            timezone: Set(tz.name().to_string()),
+            planned_arrival: Set(arrival),
+            planned_departure: Set(departure),
+            notes: Set(input.notes),
+            created_by: Set(created_by),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+        };
+
+        let stop = new_stop.insert(&txn).await?;
+        txn.commit().await?;
+        Ok(stop)
+    }
+
+    pub async fn update_stop(
+        &self,
+        room_id: Uuid,
+        stop_id: Uuid,
+        input: StopInput,
+    ) -> Result<itinerary_stop::Model> {
+        let (tz, arrival, departure) = validate_stop(&input)?;
+
+        let txn = self.db.begin().await?;
+        lock_room(&txn, room_id).await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        let stop = find_stop(&txn, room_id, stop_id).await?;
+
+        let mut stop: itinerary_stop::ActiveModel = stop.into();
+        stop.name = Set(input.name.trim().to_string());
+        stop.latitude = Set(input.latitude);
+        stop.longitude = Set(input.longitude);
+        stop.timezone = Set(tz.name().to_string());
+        stop.planned_arrival = Set(arrival);
+        stop.planned_departure = Set(departure);
+        stop.notes = Set(input.notes);
+        stop.updated_at = Set(Utc::now());
+
+        let stop = stop.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(stop)
+    }
+
+    pub async fn delete_stop(&self, room_id: Uuid, stop_id: Uuid) -> Result<()> {
+        let txn = self.db.begin().await?;
+        lock_room(&txn, room_id).await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        let stop = find_stop(&txn, room_id, stop_id).await?;
+
+        itinerary_stop::Entity::delete_by_id(stop.id)
+            .exec(&txn)
+            .await?;
+
+        // Close the gap left by the removed stop
+        let remaining = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .filter(itinerary_stop::Column::Position.gt(stop.position))
+            .all(&txn)
+            .await?;
+
+        for later in remaining {
+            let position = later.position - 1;
+            let mut later: itinerary_stop::ActiveModel = later.into();
+            later.position = Set(position);
+            later.update(&txn).await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Arrival and departure events recorded for the room, newest first
+    pub async fn get_stop_events(&self, room_id: Uuid) -> Result<Vec<stop_event::Model>> {
+        let events = stop_event::Entity::find()
+            .filter(stop_event::Column::RoomId.eq(room_id))
+            .order_by_desc(stop_event::Column::OccurredAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(events)
+    }
+
+    /// Reorders the room's itinerary. `stop_ids` must list every stop of the
+    /// room exactly once, in the desired order.
+    pub async fn reorder_stops(
+        &self,
+        room_id: Uuid,
+        stop_ids: Vec<Uuid>,
+    ) -> Result<Vec<itinerary_stop::Model>> {
+        let txn = self.db.begin().await?;
+        lock_room(&txn, room_id).await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        let stops = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .all(&txn)
+            .await?;
+
+        let mut sorted_ids = stop_ids.clone();
+        sorted_ids.sort();
+        sorted_ids.dedup();
+        let mut existing_ids: Vec<Uuid> = stops.iter().map(|s| s.id).collect();
+        existing_ids.sort();
+
+        if sorted_ids.len() != stop_ids.len() || sorted_ids != existing_ids {
+            return Err(anyhow::anyhow!(
+                "Invalid order: stop_ids must contain every stop in the itinerary exactly once"
+            ));
+        }
+
+        let now = Utc::now();
+        for stop in stops {
+            let position = stop_ids.iter().position(|id| *id == stop.id).unwrap_or_default() as i32;
+            if stop.position == position {
+                continue;
+            }
+            let mut stop: itinerary_stop::ActiveModel = stop.into();
+            stop.position = Set(position);
+            stop.updated_at = Set(now);
+            stop.update(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        self.get_stops(room_id).await
+    }
+}
+
+/// Locks the room row so concurrent itinerary edits apply one at a time and
+/// positions stay contiguous.
+async fn lock_room<C: ConnectionTrait>(db: &C, room_id: Uuid) -> Result<()> {
+    room::Entity::find_by_id(room_id)
+        .lock_exclusive()
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+    Ok(())
+}
+
+async fn find_stop<C: ConnectionTrait>(
+    db: &C,
+    room_id: Uuid,
+    stop_id: Uuid,
+) -> Result<itinerary_stop::Model> {
+    itinerary_stop::Entity::find_by_id(stop_id)
+        .filter(itinerary_stop::Column::RoomId.eq(room_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Stop not found"))
+}
+
+/// Formats a stored UTC instant as an RFC 3339 timestamp in the stop's timezone.
+pub fn to_local_time(time: DateTime<Utc>, timezone: &str) -> String {
+    match timezone.parse::<Tz>() {
+        Ok(tz) => time.with_timezone(&tz).to_rfc3339(),
+        Err(_) => time.to_rfc3339(),
+    }
+}
+
+type PlannedTime = Option<DateTime<Utc>>;
+
+fn validate_stop(input: &StopInput) -> Result<(Tz, PlannedTime, PlannedTime)> {
+    if input.name.trim().is_empty() {
+        return Err(anyhow::anyhow!("Invalid stop: name is required"));
+    }
+    if !(-90.0..=90.0).contains(&input.latitude) || !(-180.0..=180.0).contains(&input.longitude) {
+        return Err(anyhow::anyhow!("Invalid stop: coordinates out of range"));
+    }
+
+    let tz: Tz = input
+        .timezone
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid timezone: {}", input.timezone))?;
+
+    let arrival = input
+        .planned_arrival
+        .map(|local| local_to_utc(tz, local))
+        .transpose()?;
+    let departure = input
+        .planned_departure
+        .map(|local| local_to_utc(tz, local))
+        .transpose()?;
+
+    if let (Some(arrival), Some(departure)) = (arrival, departure) {
+        if departure < arrival {
+            return Err(anyhow::anyhow!(
+                "Invalid stop: planned departure is before planned arrival"
+            ));
+        }
+    }
+
+    Ok((tz, arrival, departure))
+}
+
+fn local_to_utc(tz: Tz, local: NaiveDateTime) -> Result<DateTime<Utc>> {
+    // During a DST fold the earlier of the two instants is used
+    tz.from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| anyhow::anyhow!("Invalid local time {} in {}", local, tz.name()))
+}
@@ -1,17 +1,190 @@
 use anyhow::Result;
-use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::{OnConflict, Query, SimpleExpr};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    QueryFilter, QueryOrder, Set, Statement,
+};
+use std::collections::HashMap;
+use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
 
-use crate::entities::{location, room_member};
+use crate::config::LocationConfig;
+use crate::entities::{itinerary_stop, location, location_sharing, room, room_member, stop_event};
+use crate::entities::stop_event::StopEventType;
+use crate::services::room_service::ensure_room_active;
+use crate::utils::geo::{centroid, haversine_distance, snap_to_grid, EARTH_RADIUS_M};
+
+/// A member only counts as having left a stop once they are this many times
+/// the geofence radius away, so GPS jitter at the boundary doesn't flap.
+const GEOFENCE_EXIT_FACTOR: f64 = 1.5;
+
+/// A separated member is only considered regrouped once back within this
+/// fraction of the room's max spread.
+const CONVOY_REGROUP_FACTOR: f64 = 0.8;
+
+/// Coarsest grid a member may choose for reduced-precision sharing
+const MAX_SHARING_PRECISION_METERS: f64 = 50_000.0;
+
+/// Largest radius accepted for nearby-member searches
+const MAX_NEARBY_RADIUS_METERS: f64 = 100_000.0;
+
+/// Minimum time between two convoy transitions for the same member
+const CONVOY_MIN_TRANSITION_SECS: i64 = 60;
+
+/// Fixes older than this are ignored when computing the convoy
+const CONVOY_STALE_FIX_MINUTES: i64 = 10;
+
+/// Window of recent fixes used to estimate a member's average speed
+const SPEED_WINDOW_MINUTES: i64 = 15;
+
+/// Below this average speed (m/s) a member is treated as stopped and no ETA is given
+const MIN_MOVING_SPEED_MPS: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct ConvoyMemberStatus {
+    pub user_id: Uuid,
+    pub distance_meters: f64,
+    pub separated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConvoyTransition {
+    pub user_id: Uuid,
+    pub separated: bool,
+    pub distance_meters: f64,
+    pub max_spread_meters: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberDistance {
+    pub user_a: Uuid,
+    pub user_b: Uuid,
+    pub distance_meters: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NextStopEstimate {
+    pub stop_id: Uuid,
+    pub name: String,
+    pub distance_meters: f64,
+    pub eta_seconds: Option<i64>,
+    pub eta: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberProgress {
+    pub location: location::Model,
+    pub average_speed_mps: Option<f64>,
+    pub next_stop: Option<NextStopEstimate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoomDistances {
+    pub pairs: Vec<MemberDistance>,
+    pub members: Vec<MemberProgress>,
+}
+
+/// A member's latest fix and its distance from a query point
+#[derive(Debug, Clone)]
+pub struct FixDistance {
+    pub location: location::Model,
+    pub distance_meters: f64,
+}
+
+/// Latitude/longitude rectangle; boxes crossing the antimeridian are not supported
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        (self.min_lat..=self.max_lat).contains(&lat) && (self.min_lon..=self.max_lon).contains(&lon)
+    }
+
+    /// The box grown by at least `meters` on every side
+    fn expanded(&self, meters: f64) -> BoundingBox {
+        let meters_per_degree = EARTH_RADIUS_M.to_radians();
+        let lat_pad = meters / meters_per_degree;
+        let min_lat = (self.min_lat - lat_pad).max(-90.0);
+        let max_lat = (self.max_lat + lat_pad).min(90.0);
+
+        // Degrees of longitude are shortest at the latitude furthest from the equator
+        let cos_lat = min_lat.abs().max(max_lat.abs()).to_radians().cos();
+        let lon_pad = if cos_lat > 1e-6 {
+            meters / (meters_per_degree * cos_lat)
+        } else {
+            360.0
+        };
+
+        BoundingBox {
+            min_lat,
+            min_lon: (self.min_lon - lon_pad).max(-180.0),
+            max_lat,
+            max_lon: (self.max_lon + lon_pad).min(180.0),
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct FixDistanceRow {
+    id: Uuid,
+    user_id: Uuid,
+    room_id: Uuid,
+    latitude: f64,
+    longitude: f64,
+    timestamp: DateTime<Utc>,
+    distance_meters: f64,
+}
+
+impl From<FixDistanceRow> for FixDistance {
+    fn from(row: FixDistanceRow) -> Self {
+        Self {
+            location: location::Model {
+                id: row.id,
+                user_id: row.user_id,
+                room_id: row.room_id,
+                latitude: row.latitude,
+                longitude: row.longitude,
+                timestamp: row.timestamp,
+            },
+            distance_meters: row.distance_meters,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct GeogColumn {
+    present: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SeparationState {
+    separated: bool,
+    changed_at: Option<DateTime<Utc>>,
+}
 
 pub struct LocationService {
     db: DatabaseConnection,
+    config: LocationConfig,
+    /// Debounce state per (room_id, user_id)
+    convoy_state: RwLock<HashMap<(Uuid, Uuid), SeparationState>>,
+    /// Whether `locations.geog` exists, i.e. PostGIS was enabled at startup
+    postgis: OnceCell<bool>,
 }
 
 impl LocationService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, config: LocationConfig) -> Self {
+        Self {
+            db,
+            config,
+            convoy_state: RwLock::new(HashMap::new()),
+            postgis: OnceCell::new(),
+        }
     }
 
     pub async fn update_location(
@@ -26,15 +199,10 @@ impl LocationService {
         if !is_member {
             return Err(anyhow::anyhow!("User is not a member of this room"));
         }
+        ensure_room_active(&self.db, room_id).await?;
 
-        // Delete old location for this user in this room
-        location::Entity::delete_many()
-            .filter(location::Column::UserId.eq(user_id))
-            .filter(location::Column::RoomId.eq(room_id))
-            .exec(&self.db)
-            .await?;
-
-        // Create new location
+        // Fixes are appended rather than replaced so speed and trail data
+        // can be derived from the history
         let new_location = location::ActiveModel {
             id: Set(Uuid::new_v4()),
             user_id: Set(user_id),
@@ -48,16 +216,625 @@ impl LocationService {
         Ok(location)
     }
 
-    pub async fn get_locations(&self, room_id: Uuid) -> Result<Vec<location::Model>> {
+    /// Latest fix of every member in the room as seen by `viewer_id`, newest
+    /// first. Members who are not sharing are omitted and reduced-precision
+    /// members are snapped to their grid; the viewer always sees their own
+    /// exact fix. `None` applies every member's sharing settings.
+    pub async fn get_locations(
+        &self,
+        room_id: Uuid,
+        viewer_id: Option<Uuid>,
+    ) -> Result<Vec<location::Model>> {
+        let locations = self.latest_fixes(room_id).await?;
+        apply_sharing_settings(&self.db, room_id, viewer_id, locations).await
+    }
+
+    /// Latest fix of every current member. Fixes left behind by members who
+    /// have since left the room are never returned. Each member's fix is a
+    /// single index probe, so the cost doesn't grow with the room's history.
+    async fn latest_fixes(&self, room_id: Uuid) -> Result<Vec<location::Model>> {
+        let mut locations = location::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT l.id, l.user_id, l.room_id, l.latitude, l.longitude, l.timestamp
+                FROM room_members rm
+                CROSS JOIN LATERAL (
+                    SELECT id, user_id, room_id, latitude, longitude, timestamp
+                    FROM locations
+                    WHERE room_id = rm.room_id AND user_id = rm.user_id
+                    ORDER BY timestamp DESC
+                    LIMIT 1
+                ) l
+                WHERE rm.room_id = $1
+                "#,
+                [room_id.into()],
+            ))
+            .all(&self.db)
+            .await?;
+
+        locations.sort_by_key(|loc| std::cmp::Reverse(loc.timestamp));
+        Ok(locations)
+    }
+
+    /// Fixes recorded in the room since `since`, oldest first
+    pub async fn get_recent_fixes(
+        &self,
+        room_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<location::Model>> {
         let locations = location::Entity::find()
             .filter(location::Column::RoomId.eq(room_id))
-            .order_by_desc(location::Column::Timestamp)
+            .filter(location::Column::Timestamp.gte(since))
+            .order_by_asc(location::Column::Timestamp)
             .all(&self.db)
             .await?;
 
         Ok(locations)
     }
 
+    /// Compares a fresh fix against the room's itinerary stops and records an
+    /// arrival or departure for every geofence the member crossed.
+    pub async fn detect_stop_events(
+        &self,
+        location: &location::Model,
+    ) -> Result<Vec<(stop_event::Model, itinerary_stop::Model)>> {
+        let stops = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(location.room_id))
+            .order_by_asc(itinerary_stop::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        if stops.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Latest event per stop tells whether the member is currently inside it
+        let latest_events = stop_event::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT DISTINCT ON (stop_id) *
+                FROM stop_events
+                WHERE room_id = $1 AND user_id = $2
+                ORDER BY stop_id, occurred_at DESC
+                "#,
+                [location.room_id.into(), location.user_id.into()],
+            ))
+            .all(&self.db)
+            .await?;
+
+        let inside: HashMap<Uuid, bool> = latest_events
+            .into_iter()
+            .map(|event| (event.stop_id, event.event_type == StopEventType::Arrived.as_str()))
+            .collect();
+
+        let radius = self.config.geofence_radius_meters;
+        let mut events = Vec::new();
+
+        for stop in stops {
+            let distance = haversine_distance(
+                location.latitude,
+                location.longitude,
+                stop.latitude,
+                stop.longitude,
+            );
+            let was_inside = inside.get(&stop.id).copied().unwrap_or(false);
+
+            let event_type = if !was_inside && distance <= radius {
+                StopEventType::Arrived
+            } else if was_inside && distance > radius * GEOFENCE_EXIT_FACTOR {
+                StopEventType::Departed
+            } else {
+                continue;
+            };
+
+            let new_event = stop_event::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                room_id: Set(location.room_id),
+                stop_id: Set(stop.id),
+                user_id: Set(location.user_id),
+                event_type: Set(event_type.as_str().to_string()),
+                latitude: Set(location.latitude),
+                longitude: Set(location.longitude),
+                distance_meters: Set(distance),
+                occurred_at: Set(location.timestamp),
+            };
+
+            let event = new_event.insert(&self.db).await?;
+            events.push((event, stop));
+        }
+
+        Ok(events)
+    }
+
+    /// Distance of every member with a recent fix from the convoy reference
+    /// point: the leader's position if the room has one, otherwise the centroid.
+    async fn convoy_distances(&self, room_id: Uuid) -> Result<(f64, Vec<(Uuid, f64)>)> {
+        let room = room::Entity::find_by_id(room_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        let max_spread = room
+            .max_spread_meters
+            .unwrap_or(self.config.convoy_max_spread_meters);
+
+        let cutoff = Utc::now() - Duration::minutes(CONVOY_STALE_FIX_MINUTES);
+        let fixes: Vec<location::Model> = self
+            .get_locations(room_id, None)
+            .await?
+            .into_iter()
+            .filter(|loc| loc.timestamp >= cutoff)
+            .collect();
+
+        if fixes.len() < 2 {
+            return Ok((max_spread, Vec::new()));
+        }
+
+        let leader_fix = room
+            .convoy_leader_id
+            .and_then(|leader_id| fixes.iter().find(|f| f.user_id == leader_id));
+        let reference = match leader_fix {
+            Some(fix) => (fix.latitude, fix.longitude),
+            None => {
+                let points: Vec<(f64, f64)> =
+                    fixes.iter().map(|f| (f.latitude, f.longitude)).collect();
+                match centroid(&points) {
+                    Some(point) => point,
+                    None => return Ok((max_spread, Vec::new())),
+                }
+            }
+        };
+
+        let distances = fixes
+            .iter()
+            .map(|f| {
+                let d = haversine_distance(f.latitude, f.longitude, reference.0, reference.1);
+                (f.user_id, d)
+            })
+            .collect();
+
+        Ok((max_spread, distances))
+    }
+
+    pub async fn get_convoy_status(
+        &self,
+        room_id: Uuid,
+    ) -> Result<(f64, Vec<ConvoyMemberStatus>)> {
+        let (max_spread, distances) = self.convoy_distances(room_id).await?;
+        let state = self.convoy_state.read().await;
+
+        let members = distances
+            .into_iter()
+            .map(|(user_id, distance_meters)| ConvoyMemberStatus {
+                user_id,
+                distance_meters,
+                separated: state
+                    .get(&(room_id, user_id))
+                    .map(|s| s.separated)
+                    .unwrap_or(false),
+            })
+            .collect();
+
+        Ok((max_spread, members))
+    }
+
+    /// Re-evaluates the convoy after a location update and returns the members
+    /// whose separated/regrouped state changed.
+    pub async fn update_convoy_state(&self, room_id: Uuid) -> Result<Vec<ConvoyTransition>> {
+        let (max_spread, distances) = self.convoy_distances(room_id).await?;
+        let now = Utc::now();
+        let mut state = self.convoy_state.write().await;
+        let mut transitions = Vec::new();
+
+        for (user_id, distance) in distances {
+            let entry = state.entry((room_id, user_id)).or_insert(SeparationState {
+                separated: false,
+                changed_at: None,
+            });
+
+            let crossed = if entry.separated {
+                distance < max_spread * CONVOY_REGROUP_FACTOR
+            } else {
+                distance > max_spread
+            };
+            let debounced = entry
+                .changed_at
+                .is_some_and(|t| now - t < Duration::seconds(CONVOY_MIN_TRANSITION_SECS));
+            if !crossed || debounced {
+                continue;
+            }
+
+            entry.separated = !entry.separated;
+            entry.changed_at = Some(now);
+            transitions.push(ConvoyTransition {
+                user_id,
+                separated: entry.separated,
+                distance_meters: distance,
+                max_spread_meters: max_spread,
+            });
+        }
+
+        Ok(transitions)
+    }
+
+    /// Drops the separation state of a member who left or was removed
+    pub async fn forget_convoy_member(&self, room_id: Uuid, user_id: Uuid) {
+        self.convoy_state.write().await.remove(&(room_id, user_id));
+    }
+
+    /// Drops the separation state of every member of a deleted or archived room
+    pub async fn forget_convoy_room(&self, room_id: Uuid) {
+        self.convoy_state
+            .write()
+            .await
+            .retain(|(state_room_id, _), _| *state_room_id != room_id);
+    }
+
+    /// Pairwise distances between members' latest fixes plus each member's
+    /// straight-line distance and naive ETA to the next stop they have not
+    /// yet arrived at. Sharing settings are applied for `viewer_id`.
+    pub async fn get_room_distances(
+        &self,
+        room_id: Uuid,
+        viewer_id: Uuid,
+    ) -> Result<RoomDistances> {
+        let latest = self.get_locations(room_id, Some(viewer_id)).await?;
+
+        let mut pairs = Vec::new();
+        for (i, a) in latest.iter().enumerate() {
+            for b in &latest[i + 1..] {
+                pairs.push(MemberDistance {
+                    user_a: a.user_id,
+                    user_b: b.user_id,
+                    distance_meters: haversine_distance(
+                        a.latitude,
+                        a.longitude,
+                        b.latitude,
+                        b.longitude,
+                    ),
+                });
+            }
+        }
+
+        let stops = itinerary_stop::Entity::find()
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .order_by_asc(itinerary_stop::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        let arrivals = stop_event::Entity::find()
+            .filter(stop_event::Column::RoomId.eq(room_id))
+            .filter(stop_event::Column::EventType.eq(StopEventType::Arrived.as_str()))
+            .all(&self.db)
+            .await?;
+
+        let since = Utc::now() - Duration::minutes(SPEED_WINDOW_MINUTES);
+        let recent = self.get_recent_fixes(room_id, since).await?;
+
+        let now = Utc::now();
+        let members = latest
+            .into_iter()
+            .map(|loc| {
+                let trail: Vec<&location::Model> =
+                    recent.iter().filter(|f| f.user_id == loc.user_id).collect();
+                let average_speed_mps = average_speed(&trail);
+
+                let next_stop = stops
+                    .iter()
+                    .find(|stop| {
+                        !arrivals
+                            .iter()
+                            .any(|a| a.user_id == loc.user_id && a.stop_id == stop.id)
+                    })
+                    .map(|stop| {
+                        let distance_meters = haversine_distance(
+                            loc.latitude,
+                            loc.longitude,
+                            stop.latitude,
+                            stop.longitude,
+                        );
+                        let eta_seconds = average_speed_mps
+                            .filter(|speed| *speed >= MIN_MOVING_SPEED_MPS)
+                            .map(|speed| (distance_meters / speed).round() as i64);
+
+                        NextStopEstimate {
+                            stop_id: stop.id,
+                            name: stop.name.clone(),
+                            distance_meters,
+                            eta_seconds,
+                            eta: eta_seconds.map(|secs| now + Duration::seconds(secs)),
+                        }
+                    });
+
+                MemberProgress {
+                    location: loc,
+                    average_speed_mps,
+                    next_stop,
+                }
+            })
+            .collect();
+
+        Ok(RoomDistances { pairs, members })
+    }
+
+    /// Members whose latest fix lies within `radius_m` of a point, nearest first
+    pub async fn get_nearby_members(
+        &self,
+        room_id: Uuid,
+        viewer_id: Uuid,
+        latitude: f64,
+        longitude: f64,
+        radius_m: f64,
+    ) -> Result<Vec<FixDistance>> {
+        validate_point(latitude, longitude)?;
+        if !radius_m.is_finite() || radius_m <= 0.0 || radius_m > MAX_NEARBY_RADIUS_METERS {
+            return Err(anyhow::anyhow!(
+                "Invalid radius_m: must be greater than 0 and at most {}",
+                MAX_NEARBY_RADIUS_METERS
+            ));
+        }
+
+        // Sharing settings are applied before the radius check so members are
+        // matched on the position they share, which snapping can move by up
+        // to one grid cell; the search is widened by that much.
+        let settings = sharing_settings(&self.db, room_id).await?;
+        let search_radius = radius_m + snap_margin(&settings);
+
+        let candidates = if self.postgis_enabled().await {
+            FixDistanceRow::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT l.id, l.user_id, l.room_id, l.latitude, l.longitude, l.timestamp,
+                    ST_Distance(l.geog, ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography) AS distance_meters
+                FROM room_members rm
+                CROSS JOIN LATERAL (
+                    SELECT id, user_id, room_id, latitude, longitude, timestamp, geog
+                    FROM locations
+                    WHERE room_id = rm.room_id AND user_id = rm.user_id
+                    ORDER BY timestamp DESC
+                    LIMIT 1
+                ) l
+                WHERE rm.room_id = $1
+                    AND ST_DWithin(l.geog, ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography, $4)
+                "#,
+                [room_id.into(), longitude.into(), latitude.into(), search_radius.into()],
+            ))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(FixDistance::from)
+            .collect()
+        } else {
+            self.latest_fixes(room_id)
+                .await?
+                .into_iter()
+                .map(|loc| distance_from(loc, latitude, longitude))
+                .collect()
+        };
+
+        let mut nearby = share_distances(&settings, viewer_id, candidates, latitude, longitude);
+        nearby.retain(|d| d.distance_meters <= radius_m);
+        Ok(nearby)
+    }
+
+    /// Each member's distance from an itinerary stop, nearest first
+    pub async fn get_stop_distances(
+        &self,
+        room_id: Uuid,
+        stop_id: Uuid,
+        viewer_id: Uuid,
+    ) -> Result<Vec<FixDistance>> {
+        let stop = itinerary_stop::Entity::find_by_id(stop_id)
+            .filter(itinerary_stop::Column::RoomId.eq(room_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Stop not found"))?;
+
+        let candidates = if self.postgis_enabled().await {
+            FixDistanceRow::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                SELECT l.id, l.user_id, l.room_id, l.latitude, l.longitude, l.timestamp,
+                    ST_Distance(l.geog, ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography) AS distance_meters
+                FROM room_members rm
+                CROSS JOIN LATERAL (
+                    SELECT id, user_id, room_id, latitude, longitude, timestamp, geog
+                    FROM locations
+                    WHERE room_id = rm.room_id AND user_id = rm.user_id
+                    ORDER BY timestamp DESC
+                    LIMIT 1
+                ) l
+                WHERE rm.room_id = $1
+                "#,
+                [room_id.into(), stop.longitude.into(), stop.latitude.into()],
+            ))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(FixDistance::from)
+            .collect()
+        } else {
+            self.latest_fixes(room_id)
+                .await?
+                .into_iter()
+                .map(|loc| distance_from(loc, stop.latitude, stop.longitude))
+                .collect()
+        };
+
+        let settings = sharing_settings(&self.db, room_id).await?;
+        Ok(share_distances(&settings, viewer_id, candidates, stop.latitude, stop.longitude))
+    }
+
+    /// Fixes recorded inside a bounding box, oldest first
+    pub async fn get_fixes_in_bbox(
+        &self,
+        room_id: Uuid,
+        viewer_id: Uuid,
+        bbox: BoundingBox,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<location::Model>> {
+        validate_point(bbox.min_lat, bbox.min_lon)?;
+        validate_point(bbox.max_lat, bbox.max_lon)?;
+        if bbox.min_lat > bbox.max_lat || bbox.min_lon > bbox.max_lon {
+            return Err(anyhow::anyhow!("Invalid bounding box: minimums must not exceed maximums"));
+        }
+
+        // As for nearby members, fixes are matched on their shared position;
+        // the search box is widened by the most snapping can move a fix
+        let settings = sharing_settings(&self.db, room_id).await?;
+        let search = bbox.expanded(snap_margin(&settings));
+
+        let fixes = if self.postgis_enabled().await {
+            location::Entity::find()
+                .from_raw_sql(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    r#"
+                    SELECT id, user_id, room_id, latitude, longitude, timestamp
+                    FROM locations
+                    WHERE room_id = $1
+                        AND user_id IN (SELECT user_id FROM room_members WHERE room_id = $1)
+                        AND ST_Intersects(geog, ST_MakeEnvelope($2, $3, $4, $5, 4326)::geography)
+                        AND ($6::timestamptz IS NULL OR timestamp >= $6)
+                        AND ($7::timestamptz IS NULL OR timestamp <= $7)
+                    ORDER BY timestamp ASC
+                    "#,
+                    [
+                        room_id.into(),
+                        search.min_lon.into(),
+                        search.min_lat.into(),
+                        search.max_lon.into(),
+                        search.max_lat.into(),
+                        since.into(),
+                        until.into(),
+                    ],
+                ))
+                .all(&self.db)
+                .await?
+        } else {
+            let mut select = location::Entity::find()
+                .filter(location::Column::RoomId.eq(room_id))
+                .filter(by_current_members(room_id))
+                .filter(location::Column::Latitude.between(search.min_lat, search.max_lat))
+                .filter(location::Column::Longitude.between(search.min_lon, search.max_lon));
+            if let Some(since) = since {
+                select = select.filter(location::Column::Timestamp.gte(since));
+            }
+            if let Some(until) = until {
+                select = select.filter(location::Column::Timestamp.lte(until));
+            }
+            select
+                .order_by_asc(location::Column::Timestamp)
+                .all(&self.db)
+                .await?
+        };
+
+        let mut fixes = share_fixes(&settings, Some(viewer_id), fixes, Utc::now());
+        fixes.retain(|f| bbox.contains(f.latitude, f.longitude));
+        Ok(fixes)
+    }
+
+    async fn postgis_enabled(&self) -> bool {
+        *self
+            .postgis
+            .get_or_init(|| async {
+                let column = GeogColumn::find_by_statement(Statement::from_string(
+                    DbBackend::Postgres,
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM information_schema.columns
+                        WHERE table_name = 'locations' AND column_name = 'geog'
+                    ) AS present
+                    "#,
+                ))
+                .one(&self.db)
+                .await;
+
+                match column {
+                    Ok(column) => column.is_some_and(|c| c.present),
+                    Err(e) => {
+                        tracing::warn!("Failed to detect PostGIS support: {}", e);
+                        false
+                    }
+                }
+            })
+            .await
+    }
+
+    /// A member's sharing settings in the room; members who never changed
+    /// them share their exact position.
+    pub async fn get_sharing_settings(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<location_sharing::Model> {
+        let settings = location_sharing::Entity::find_by_id((room_id, user_id))
+            .one(&self.db)
+            .await?;
+
+        Ok(settings.unwrap_or_else(|| location_sharing::Model {
+            room_id,
+            user_id,
+            paused: false,
+            precision_meters: None,
+            expires_at: None,
+            updated_at: Utc::now(),
+        }))
+    }
+
+    pub async fn update_sharing_settings(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+        paused: bool,
+        precision_meters: Option<f64>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<location_sharing::Model> {
+        if precision_meters
+            .is_some_and(|p| !p.is_finite() || p <= 0.0 || p > MAX_SHARING_PRECISION_METERS)
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid precision_meters: must be greater than 0 and at most {}",
+                MAX_SHARING_PRECISION_METERS
+            ));
+        }
+
+        let settings = location_sharing::ActiveModel {
+            room_id: Set(room_id),
+            user_id: Set(user_id),
+            paused: Set(paused),
+            precision_meters: Set(precision_meters),
+            expires_at: Set(expires_at),
+            updated_at: Set(Utc::now()),
+        };
+        location_sharing::Entity::insert(settings)
+            .on_conflict(
+                OnConflict::columns([
+                    location_sharing::Column::RoomId,
+                    location_sharing::Column::UserId,
+                ])
+                .update_columns([
+                    location_sharing::Column::Paused,
+                    location_sharing::Column::PrecisionMeters,
+                    location_sharing::Column::ExpiresAt,
+                    location_sharing::Column::UpdatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+
+        self.get_sharing_settings(room_id, user_id).await
+    }
+
+    /// How a member's fix is shown to the rest of the room: `None` when they
+    /// are not sharing, snapped when they share with reduced precision.
+    pub async fn shared_fix(&self, fix: &location::Model) -> Result<Option<location::Model>> {
+        let settings = self.get_sharing_settings(fix.room_id, fix.user_id).await?;
+        Ok(share_fix(Some(&settings), fix.clone(), Utc::now()))
+    }
+
     pub async fn verify_membership(&self, room_id: Uuid, user_id: Uuid) -> Result<bool> {
         let member = room_member::Entity::find()
             .filter(room_member::Column::RoomId.eq(room_id))
@@ -68,3 +845,248 @@ impl LocationService {
         Ok(member.is_some())
     }
 }
+
+/// Applies each member's sharing settings to fixes recorded in `room_id`,
+/// leaving the viewer's own fixes untouched.
+pub async fn apply_sharing_settings(
+    db: &DatabaseConnection,
+    room_id: Uuid,
+    viewer_id: Option<Uuid>,
+    fixes: Vec<location::Model>,
+) -> Result<Vec<location::Model>> {
+    let settings = sharing_settings(db, room_id).await?;
+    Ok(share_fixes(&settings, viewer_id, fixes, Utc::now()))
+}
+
+/// Restricts a `locations` query to fixes of the room's current members, so
+/// members who left or were removed stop appearing
+pub fn by_current_members(room_id: Uuid) -> SimpleExpr {
+    location::Column::UserId.in_subquery(
+        Query::select()
+            .column(room_member::Column::UserId)
+            .from(room_member::Entity)
+            .and_where(room_member::Column::RoomId.eq(room_id))
+            .to_owned(),
+    )
+}
+
+/// Sharing settings of the room's members, keyed by user id
+pub async fn sharing_settings(
+    db: &DatabaseConnection,
+    room_id: Uuid,
+) -> Result<HashMap<Uuid, location_sharing::Model>> {
+    Ok(location_sharing::Entity::find()
+        .filter(location_sharing::Column::RoomId.eq(room_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| (s.user_id, s))
+        .collect())
+}
+
+/// The most snapping can move any member's fix: one cell of the coarsest grid
+fn snap_margin(settings: &HashMap<Uuid, location_sharing::Model>) -> f64 {
+    settings
+        .values()
+        .filter_map(|s| s.precision_meters)
+        .fold(0.0, f64::max)
+}
+
+fn share_fixes(
+    settings: &HashMap<Uuid, location_sharing::Model>,
+    viewer_id: Option<Uuid>,
+    fixes: Vec<location::Model>,
+    now: DateTime<Utc>,
+) -> Vec<location::Model> {
+    fixes
+        .into_iter()
+        .filter_map(|fix| {
+            if viewer_id == Some(fix.user_id) {
+                return Some(fix);
+            }
+            share_fix(settings.get(&fix.user_id), fix, now)
+        })
+        .collect()
+}
+
+/// Applies sharing settings to distance results, recomputing the distance
+/// for fixes that were snapped to a coarser grid. Sorted nearest first.
+fn share_distances(
+    settings: &HashMap<Uuid, location_sharing::Model>,
+    viewer_id: Uuid,
+    candidates: Vec<FixDistance>,
+    latitude: f64,
+    longitude: f64,
+) -> Vec<FixDistance> {
+    let now = Utc::now();
+    let mut shared: Vec<FixDistance> = candidates
+        .into_iter()
+        .filter_map(|d| {
+            if d.location.user_id == viewer_id {
+                return Some(d);
+            }
+            let fix = share_fix(settings.get(&d.location.user_id), d.location.clone(), now)?;
+            Some(if fix == d.location {
+                d
+            } else {
+                distance_from(fix, latitude, longitude)
+            })
+        })
+        .collect();
+
+    shared.sort_by(|a, b| a.distance_meters.total_cmp(&b.distance_meters));
+    shared
+}
+
+fn share_fix(
+    settings: Option<&location_sharing::Model>,
+    mut fix: location::Model,
+    now: DateTime<Utc>,
+) -> Option<location::Model> {
+    let Some(settings) = settings else {
+        return Some(fix);
+    };
+    if !settings.is_sharing(now) {
+        return None;
+    }
+    if let Some(precision) = settings.precision_meters {
+        (fix.latitude, fix.longitude) = snap_to_grid(fix.latitude, fix.longitude, precision);
+    }
+    Some(fix)
+}
+
+fn validate_point(latitude: f64, longitude: f64) -> Result<()> {
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(anyhow::anyhow!("Invalid coordinates: latitude must be within ±90 and longitude within ±180"));
+    }
+    Ok(())
+}
+
+fn distance_from(location: location::Model, latitude: f64, longitude: f64) -> FixDistance {
+    let distance_meters =
+        haversine_distance(location.latitude, location.longitude, latitude, longitude);
+    FixDistance {
+        location,
+        distance_meters,
+    }
+}
+
+/// Path length over elapsed time for a chronologically ordered trail
+fn average_speed(trail: &[&location::Model]) -> Option<f64> {
+    let (first, last) = (trail.first()?, trail.last()?);
+    let elapsed = (last.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0;
+    if elapsed <= 0.0 {
+        return None;
+    }
+
+    let path: f64 = trail
+        .windows(2)
+        .map(|w| haversine_distance(w[0].latitude, w[0].longitude, w[1].latitude, w[1].longitude))
+        .sum();
+
+    Some(path / elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::user;
+    use sea_orm::Database;
+
+    async fn insert_user(db: &DatabaseConnection) -> Uuid {
+        let id = Uuid::new_v4();
+        user::ActiveModel {
+            id: Set(id),
+            name: Set("Test".to_string()),
+            email: Set(format!("{}@example.com", id)),
+            password_hash: Set(String::new()),
+            avatar: Set(None),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn insert_member(db: &DatabaseConnection, room_id: Uuid, user_id: Uuid) {
+        room_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            room_id: Set(room_id),
+            user_id: Set(user_id),
+            joined_at: Set(Utc::now()),
+            role: Set("member".to_string()),
+            last_read_message_id: Set(None),
+            last_read_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    /// Needs a Postgres database in `TEST_DATABASE_URL`; skipped when unset
+    #[tokio::test]
+    async fn test_removed_member_fix_is_omitted() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let db = Database::connect(url).await.unwrap();
+        crate::run_migrations_seaorm(&db).await.unwrap();
+
+        let (stays, leaves) = (insert_user(&db).await, insert_user(&db).await);
+        let room_id = Uuid::new_v4();
+        room::ActiveModel {
+            id: Set(room_id),
+            name: Set("Test".to_string()),
+            description: Set(None),
+            created_by: Set(stays),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+            is_active: Set(true),
+            max_spread_meters: Set(None),
+            convoy_leader_id: Set(None),
+            approval_required: Set(false),
+            max_members: Set(None),
+            archived_at: Set(None),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        insert_member(&db, room_id, stays).await;
+        insert_member(&db, room_id, leaves).await;
+
+        let service = LocationService::new(
+            db.clone(),
+            LocationConfig {
+                geofence_radius_meters: 100.0,
+                convoy_max_spread_meters: 5_000.0,
+                trail_compaction_age_hours: 24,
+                trail_compaction_tolerance_meters: 10.0,
+                trail_compaction_interval_minutes: 60,
+                retention_days_after_archive: 30,
+                retention_interval_minutes: 1440,
+                retention_dry_run: true,
+            },
+        );
+        service.update_location(stays, room_id, 40.0, -105.0).await.unwrap();
+        service.update_location(leaves, room_id, 40.1, -105.1).await.unwrap();
+
+        room_member::Entity::delete_many()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(room_member::Column::UserId.eq(leaves))
+            .exec(&db)
+            .await
+            .unwrap();
+
+        let fixes = service.get_locations(room_id, None).await.unwrap();
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].user_id, stays);
+
+        let nearby = service
+            .get_nearby_members(room_id, stays, 40.0, -105.0, 50_000.0)
+            .await
+            .unwrap();
+        assert!(nearby.iter().all(|d| d.location.user_id != leaves));
+    }
+}
@@ -1,9 +1,115 @@
 use anyhow::Result;
-use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set};
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, OnConflict, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbBackend, EntityTrait,
+    FromQueryResult, PaginatorTrait, QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use crate::entities::{message, room_member};
+use crate::entities::message::{LocationPin, MessageType, PollPayload, SystemEvent};
+use crate::entities::{
+    message, message_mention, message_pin, message_reaction, room_member, sos_alert, user,
+};
+use crate::services::room_service::ensure_room_active;
+use crate::utils::emoji::is_valid_reaction;
+use crate::utils::mention::find_mentions;
+
+/// Longest accepted search query, in characters
+const MAX_SEARCH_QUERY_CHARS: usize = 256;
+
+const MAX_PIN_LABEL_CHARS: usize = 100;
+const MAX_POLL_QUESTION_CHARS: usize = 200;
+const MAX_POLL_OPTION_CHARS: usize = 100;
+const MAX_POLL_OPTIONS: usize = 10;
+
+/// Content of a message about to be posted
+#[derive(Debug, Clone)]
+pub struct MessageDraft {
+    pub message_type: MessageType,
+    pub text: Option<String>,
+    pub image_url: Option<String>,
+    pub payload: Option<serde_json::Value>,
+    pub reply_to_id: Option<Uuid>,
+}
+
+impl MessageDraft {
+    pub fn system(text: String, event: SystemEvent) -> Self {
+        Self {
+            message_type: MessageType::System,
+            text: Some(text),
+            image_url: None,
+            payload: serde_json::to_value(event).ok(),
+            reply_to_id: None,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct UnreadCount {
+    room_id: Uuid,
+    unread: i64,
+}
+
+/// How many members reacted to a message with one emoji
+#[derive(Debug, Clone, Serialize)]
+pub struct ReactionCount {
+    pub emoji: String,
+    pub count: u64,
+    /// Whether the viewing user is among them
+    pub reacted: bool,
+}
+
+/// A message matching a search, with its relevance and an HTML-escaped
+/// excerpt where matched words are wrapped in `<mark>`
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct SearchHit {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+/// Keyset position after the last hit of a search page. Hits are ordered by
+/// rank, then newest first, with the id as a tiebreaker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchCursor {
+    pub rank: f32,
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}_{}", self.rank, self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn parse(cursor: &str) -> Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid cursor");
+        let mut parts = cursor.splitn(3, '_');
+        let rank = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let created_at = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        let id = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+
+        Ok(Self { rank, created_at, id })
+    }
+}
+
+impl From<&SearchHit> for SearchCursor {
+    fn from(hit: &SearchHit) -> Self {
+        Self {
+            rank: hit.rank,
+            created_at: hit.created_at,
+            id: hit.id,
+        }
+    }
+}
 
 pub struct MessageService {
     db: DatabaseConnection,
@@ -14,26 +120,72 @@ impl MessageService {
         Self { db }
     }
 
+    /// Stores a message and records the room members it mentions. Returns
+    /// the message and the mentioned user ids.
     pub async fn send_message(
         &self,
         room_id: Uuid,
         user_id: Uuid,
-        text: Option<String>,
-        image_url: Option<String>,
-        message_type: String,
-    ) -> Result<message::Model> {
+        draft: MessageDraft,
+    ) -> Result<(message::Model, Vec<Uuid>)> {
+        let (text, payload) = validate_content(&draft)?;
+        let txn = self.db.begin().await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        if let Some(parent_id) = draft.reply_to_id {
+            let parent = message::Entity::find_by_id(parent_id)
+                .filter(message::Column::RoomId.eq(room_id))
+                .one(&txn)
+                .await?;
+            if parent.is_none() {
+                return Err(anyhow::anyhow!("Invalid reply_to_id: no such message in this room"));
+            }
+        }
+
         let new_message = message::ActiveModel {
             id: Set(Uuid::new_v4()),
             room_id: Set(room_id),
             user_id: Set(user_id),
             text: Set(text),
-            image_url: Set(image_url),
-            message_type: Set(message_type),
+            image_url: Set(draft.image_url),
+            message_type: Set(draft.message_type.as_str().to_string()),
             created_at: Set(Utc::now()),
+            reply_to_id: Set(draft.reply_to_id),
+            payload: Set(payload),
         };
+        let message = new_message.insert(&txn).await?;
 
-        let message = new_message.insert(&self.db).await?;
-        Ok(message)
+        // System messages name people without notifying them
+        let mentions = match &message.text {
+            Some(text) if draft.message_type != MessageType::System => {
+                let members: Vec<(Uuid, String)> = room_member::Entity::find()
+                    .find_also_related(user::Entity)
+                    .filter(room_member::Column::RoomId.eq(room_id))
+                    .filter(room_member::Column::UserId.ne(user_id))
+                    .all(&txn)
+                    .await?
+                    .into_iter()
+                    .filter_map(|(member, user)| user.map(|user| (member.user_id, user.name)))
+                    .collect();
+                find_mentions(text, &members)
+            }
+            _ => Vec::new(),
+        };
+
+        if !mentions.is_empty() {
+            let records = mentions.iter().map(|&mentioned| message_mention::ActiveModel {
+                message_id: Set(message.id),
+                user_id: Set(mentioned),
+                room_id: Set(room_id),
+                created_at: Set(message.created_at),
+            });
+            message_mention::Entity::insert_many(records)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        Ok((message, mentions))
     }
 
     pub async fn get_messages(
@@ -53,6 +205,454 @@ impl MessageService {
         Ok((messages, total_pages))
     }
 
+    pub async fn get_message(&self, room_id: Uuid, message_id: Uuid) -> Result<message::Model> {
+        message::Entity::find_by_id(message_id)
+            .filter(message::Column::RoomId.eq(room_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Message not found"))
+    }
+
+    /// Replies to a message, oldest first
+    pub async fn get_replies(
+        &self,
+        room_id: Uuid,
+        parent_id: Uuid,
+        page: u64,
+        page_size: u64,
+    ) -> Result<(Vec<message::Model>, u64)> {
+        let paginator = message::Entity::find()
+            .filter(message::Column::RoomId.eq(room_id))
+            .filter(message::Column::ReplyToId.eq(parent_id))
+            .order_by_asc(message::Column::CreatedAt)
+            .paginate(&self.db, page_size);
+
+        let total_pages = paginator.num_pages().await?;
+        let replies = paginator.fetch_page(page).await?;
+
+        Ok((replies, total_pages))
+    }
+
+    pub async fn get_messages_by_ids(&self, ids: Vec<Uuid>) -> Result<Vec<message::Model>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let messages = message::Entity::find()
+            .filter(message::Column::Id.is_in(ids))
+            .all(&self.db)
+            .await?;
+
+        Ok(messages)
+    }
+
+    /// Full-text search over the room's messages, best matches first.
+    /// `query` uses web search syntax (quoted phrases, `or`, `-word`).
+    /// Returns up to `limit` hits and the cursor for the next page.
+    pub async fn search(
+        &self,
+        room_id: Uuid,
+        query: &str,
+        cursor: Option<SearchCursor>,
+        limit: u64,
+    ) -> Result<(Vec<SearchHit>, Option<SearchCursor>)> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(anyhow::anyhow!("Invalid q: search text is required"));
+        }
+        if query.chars().count() > MAX_SEARCH_QUERY_CHARS {
+            return Err(anyhow::anyhow!(
+                "Invalid q: must be at most {} characters",
+                MAX_SEARCH_QUERY_CHARS
+            ));
+        }
+
+        // The tsvector expression matches idx_messages_text_search. Text is
+        // HTML-escaped before highlighting so snippets are safe to render.
+        let mut sql = String::from(
+            r#"
+            SELECT m.id, m.created_at,
+                ts_rank(to_tsvector('english', COALESCE(m.text, '')), q) AS rank,
+                ts_headline(
+                    'english',
+                    replace(replace(replace(m.text, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'),
+                    q,
+                    'StartSel=<mark>, StopSel=</mark>, MinWords=8, MaxWords=24, MaxFragments=2'
+                ) AS snippet
+            FROM messages m, websearch_to_tsquery('english', $2) q
+            WHERE m.room_id = $1
+                AND to_tsvector('english', COALESCE(m.text, '')) @@ q
+            "#,
+        );
+        let mut values = vec![room_id.into(), query.into(), (limit as i64 + 1).into()];
+        if let Some(cursor) = cursor {
+            sql.push_str(
+                "AND (ts_rank(to_tsvector('english', COALESCE(m.text, '')), q), m.created_at, m.id) < ($4, $5, $6)\n",
+            );
+            values.extend([cursor.rank.into(), cursor.created_at.into(), cursor.id.into()]);
+        }
+        sql.push_str("ORDER BY rank DESC, m.created_at DESC, m.id DESC LIMIT $3");
+
+        let mut hits = SearchHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(&self.db)
+        .await?;
+
+        let next_cursor = if hits.len() as u64 > limit {
+            hits.truncate(limit as usize);
+            hits.last().map(SearchCursor::from)
+        } else {
+            None
+        };
+
+        Ok((hits, next_cursor))
+    }
+
+    /// Users mentioned in each of the given messages
+    pub async fn get_mentions(&self, message_ids: Vec<Uuid>) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        if message_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut mentions: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for mention in message_mention::Entity::find()
+            .filter(message_mention::Column::MessageId.is_in(message_ids))
+            .all(&self.db)
+            .await?
+        {
+            mentions.entry(mention.message_id).or_default().push(mention.user_id);
+        }
+
+        Ok(mentions)
+    }
+
+    /// Messages mentioning the user, newest first, limited to rooms they
+    /// still belong to
+    pub async fn get_user_mentions(
+        &self,
+        user_id: Uuid,
+        page: u64,
+        page_size: u64,
+    ) -> Result<(Vec<message::Model>, u64)> {
+        let paginator = message::Entity::find()
+            .filter(
+                message::Column::Id.in_subquery(
+                    Query::select()
+                        .column(message_mention::Column::MessageId)
+                        .from(message_mention::Entity)
+                        .and_where(message_mention::Column::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(
+                message::Column::RoomId.in_subquery(
+                    Query::select()
+                        .column(room_member::Column::RoomId)
+                        .from(room_member::Entity)
+                        .and_where(room_member::Column::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .order_by_desc(message::Column::CreatedAt)
+            .paginate(&self.db, page_size);
+
+        let total_pages = paginator.num_pages().await?;
+        let messages = paginator.fetch_page(page).await?;
+
+        Ok((messages, total_pages))
+    }
+
+    /// Most recent message in the room, if any
+    pub async fn get_last_message(&self, room_id: Uuid) -> Result<Option<message::Model>> {
+        let message = message::Entity::find()
+            .filter(message::Column::RoomId.eq(room_id))
+            .order_by_desc(message::Column::CreatedAt)
+            .order_by_desc(message::Column::Id)
+            .one(&self.db)
+            .await?;
+
+        Ok(message)
+    }
+
+    /// Messages from other members after a `(created_at, id)` read cursor;
+    /// without an id only strictly later messages count
+    pub async fn count_unread(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+        (read_at, read_id): (DateTime<Utc>, Option<Uuid>),
+    ) -> Result<u64> {
+        let mut after = Condition::any().add(message::Column::CreatedAt.gt(read_at));
+        if let Some(read_id) = read_id {
+            after = after.add(
+                Condition::all()
+                    .add(message::Column::CreatedAt.eq(read_at))
+                    .add(message::Column::Id.gt(read_id)),
+            );
+        }
+
+        let count = message::Entity::find()
+            .filter(message::Column::RoomId.eq(room_id))
+            .filter(message::Column::UserId.ne(user_id))
+            .filter(after)
+            .count(&self.db)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Unread message count for each room the user belongs to
+    pub async fn count_unread_by_room(&self, user_id: Uuid) -> Result<HashMap<Uuid, u64>> {
+        let rows = UnreadCount::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT rm.room_id, COUNT(m.id) AS unread
+            FROM room_members rm
+            LEFT JOIN messages m
+                ON m.room_id = rm.room_id
+                AND m.user_id <> rm.user_id
+                AND (
+                    m.created_at > COALESCE(rm.last_read_at, rm.joined_at)
+                    OR (m.created_at = rm.last_read_at AND m.id > rm.last_read_message_id)
+                )
+            WHERE rm.user_id = $1
+            GROUP BY rm.room_id
+            "#,
+            [user_id.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.room_id, row.unread.max(0) as u64))
+            .collect())
+    }
+
+    /// Moves the user's read position up to `message_id`, or to the latest
+    /// message when omitted. Returns the message now marked as read, or
+    /// `None` if the position didn't move forward.
+    pub async fn mark_read(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> Result<Option<message::Model>> {
+        let message = match message_id {
+            Some(message_id) => Some(self.get_message(room_id, message_id).await?),
+            None => self.get_last_message(room_id).await?,
+        };
+        let Some(message) = message else {
+            return Ok(None);
+        };
+
+        // Conditional update so concurrent reads never move the (created_at, id)
+        // position back
+        let result = room_member::Entity::update_many()
+            .col_expr(room_member::Column::LastReadMessageId, Expr::value(Some(message.id)))
+            .col_expr(room_member::Column::LastReadAt, Expr::value(Some(message.created_at)))
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(room_member::Column::UserId.eq(user_id))
+            .filter(
+                Condition::any()
+                    .add(room_member::Column::LastReadAt.is_null())
+                    .add(room_member::Column::LastReadAt.lt(message.created_at))
+                    .add(
+                        Condition::all()
+                            .add(room_member::Column::LastReadAt.eq(message.created_at))
+                            .add(
+                                Condition::any()
+                                    .add(room_member::Column::LastReadMessageId.is_null())
+                                    .add(room_member::Column::LastReadMessageId.lt(message.id)),
+                            ),
+                    ),
+            )
+            .exec(&self.db)
+            .await?;
+
+        Ok((result.rows_affected > 0).then_some(message))
+    }
+
+    /// Adds the user's reaction to a message. Returns `false` if they had
+    /// already reacted with this emoji.
+    pub async fn add_reaction(
+        &self,
+        room_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        emoji: &str,
+    ) -> Result<bool> {
+        if !is_valid_reaction(emoji) {
+            return Err(anyhow::anyhow!("Invalid emoji"));
+        }
+        ensure_room_active(&self.db, room_id).await?;
+        let message = self.get_message(room_id, message_id).await?;
+
+        let reaction = message_reaction::ActiveModel {
+            message_id: Set(message.id),
+            user_id: Set(user_id),
+            emoji: Set(emoji.to_string()),
+            created_at: Set(Utc::now()),
+        };
+        let inserted = message_reaction::Entity::insert(reaction)
+            .on_conflict(
+                OnConflict::columns([
+                    message_reaction::Column::MessageId,
+                    message_reaction::Column::UserId,
+                    message_reaction::Column::Emoji,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Removes the user's reaction. Returns `false` if there was none.
+    pub async fn remove_reaction(
+        &self,
+        room_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        emoji: &str,
+    ) -> Result<bool> {
+        ensure_room_active(&self.db, room_id).await?;
+        let message = self.get_message(room_id, message_id).await?;
+
+        let result = message_reaction::Entity::delete_many()
+            .filter(message_reaction::Column::MessageId.eq(message.id))
+            .filter(message_reaction::Column::UserId.eq(user_id))
+            .filter(message_reaction::Column::Emoji.eq(emoji))
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Reaction counts for each of the given messages, in the order each
+    /// emoji was first used. `viewer_id` decides the `reacted` flag.
+    pub async fn get_reactions(
+        &self,
+        message_ids: Vec<Uuid>,
+        viewer_id: Uuid,
+    ) -> Result<HashMap<Uuid, Vec<ReactionCount>>> {
+        if message_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let reactions = message_reaction::Entity::find()
+            .filter(message_reaction::Column::MessageId.is_in(message_ids))
+            .order_by_asc(message_reaction::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        let mut counts: HashMap<Uuid, Vec<ReactionCount>> = HashMap::new();
+        for reaction in reactions {
+            let message_counts = counts.entry(reaction.message_id).or_default();
+            let reacted = reaction.user_id == viewer_id;
+            match message_counts.iter_mut().find(|c| c.emoji == reaction.emoji) {
+                Some(count) => {
+                    count.count += 1;
+                    count.reacted |= reacted;
+                }
+                None => message_counts.push(ReactionCount {
+                    emoji: reaction.emoji,
+                    count: 1,
+                    reacted,
+                }),
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Pins a message to the top of its room. Returns `false` if it was
+    /// already pinned.
+    pub async fn pin_message(
+        &self,
+        room_id: Uuid,
+        message_id: Uuid,
+        pinned_by: Uuid,
+    ) -> Result<bool> {
+        ensure_room_active(&self.db, room_id).await?;
+        let message = self.get_message(room_id, message_id).await?;
+
+        let pin = message_pin::ActiveModel {
+            message_id: Set(message.id),
+            room_id: Set(room_id),
+            pinned_by: Set(Some(pinned_by)),
+            pinned_at: Set(Utc::now()),
+        };
+        let inserted = message_pin::Entity::insert(pin)
+            .on_conflict(
+                OnConflict::column(message_pin::Column::MessageId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Returns `false` if the message wasn't pinned
+    pub async fn unpin_message(&self, room_id: Uuid, message_id: Uuid) -> Result<bool> {
+        ensure_room_active(&self.db, room_id).await?;
+        let message = self.get_message(room_id, message_id).await?;
+
+        // An SOS message stays pinned until its alert is acknowledged
+        let active_sos = sos_alert::Entity::find()
+            .filter(sos_alert::Column::MessageId.eq(message.id))
+            .filter(sos_alert::Column::AcknowledgedAt.is_null())
+            .one(&self.db)
+            .await?;
+        if active_sos.is_some() {
+            return Err(anyhow::anyhow!("Invalid message: acknowledge the SOS alert to unpin it"));
+        }
+
+        let result = message_pin::Entity::delete_many()
+            .filter(message_pin::Column::MessageId.eq(message.id))
+            .filter(message_pin::Column::RoomId.eq(room_id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Pinned messages in the order they were pinned
+    pub async fn get_pins(
+        &self,
+        room_id: Uuid,
+    ) -> Result<Vec<(message_pin::Model, message::Model)>> {
+        let pins = message_pin::Entity::find()
+            .filter(message_pin::Column::RoomId.eq(room_id))
+            .order_by_asc(message_pin::Column::PinnedAt)
+            .find_also_related(message::Entity)
+            .all(&self.db)
+            .await?;
+
+        Ok(pins
+            .into_iter()
+            .filter_map(|(pin, message)| message.map(|message| (pin, message)))
+            .collect())
+    }
+
+    /// Deletes the message along with its pin. Returns whether it was pinned.
+    pub async fn delete_message(&self, message_id: Uuid) -> Result<bool> {
+        let txn = self.db.begin().await?;
+
+        let pin = message_pin::Entity::find_by_id(message_id).one(&txn).await?;
+        message::Entity::delete_by_id(message_id).exec(&txn).await?;
+
+        txn.commit().await?;
+        Ok(pin.is_some())
+    }
+
     pub async fn verify_membership(&self, room_id: Uuid, user_id: Uuid) -> Result<bool> {
         let member = room_member::Entity::find()
             .filter(room_member::Column::RoomId.eq(room_id))
@@ -63,3 +663,176 @@ impl MessageService {
         Ok(member.is_some())
     }
 }
+
+/// Checks a draft's content against its type. Returns the text to store,
+/// trimmed, and the normalized payload. Polls without text use their
+/// question so they show up in previews and search.
+fn validate_content(
+    draft: &MessageDraft,
+) -> Result<(Option<String>, Option<serde_json::Value>)> {
+    let text = draft
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string);
+
+    if draft.image_url.is_some() && draft.message_type != MessageType::Image {
+        return Err(anyhow::anyhow!("Invalid image_url: only allowed on image messages"));
+    }
+
+    let payload = match draft.message_type {
+        MessageType::Text | MessageType::Image => {
+            if draft.payload.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Invalid payload: not allowed on {} messages",
+                    draft.message_type.as_str()
+                ));
+            }
+            if draft.message_type == MessageType::Text && text.is_none() {
+                return Err(anyhow::anyhow!("Invalid text: required for text messages"));
+            }
+            if draft.message_type == MessageType::Image && draft.image_url.is_none() {
+                return Err(anyhow::anyhow!("Invalid image_url: required for image messages"));
+            }
+            return Ok((text, None));
+        }
+        MessageType::Location => {
+            let mut pin: LocationPin = parse_payload(draft)?;
+            if !pin.latitude.is_finite() || !(-90.0..=90.0).contains(&pin.latitude) {
+                return Err(anyhow::anyhow!("Invalid payload: latitude must be between -90 and 90"));
+            }
+            if !pin.longitude.is_finite() || !(-180.0..=180.0).contains(&pin.longitude) {
+                return Err(anyhow::anyhow!("Invalid payload: longitude must be between -180 and 180"));
+            }
+            pin.label = pin
+                .label
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty());
+            if pin.label.as_ref().is_some_and(|l| l.chars().count() > MAX_PIN_LABEL_CHARS) {
+                return Err(anyhow::anyhow!(
+                    "Invalid payload: label must be at most {} characters",
+                    MAX_PIN_LABEL_CHARS
+                ));
+            }
+            serde_json::to_value(pin)?
+        }
+        MessageType::Poll => {
+            let mut poll: PollPayload = parse_payload(draft)?;
+            poll.question = poll.question.trim().to_string();
+            if poll.question.is_empty() || poll.question.chars().count() > MAX_POLL_QUESTION_CHARS {
+                return Err(anyhow::anyhow!(
+                    "Invalid payload: question must be 1 to {} characters",
+                    MAX_POLL_QUESTION_CHARS
+                ));
+            }
+            poll.options = poll.options.iter().map(|o| o.trim().to_string()).collect();
+            if !(2..=MAX_POLL_OPTIONS).contains(&poll.options.len()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid payload: a poll needs 2 to {} options",
+                    MAX_POLL_OPTIONS
+                ));
+            }
+            if poll
+                .options
+                .iter()
+                .any(|o| o.is_empty() || o.chars().count() > MAX_POLL_OPTION_CHARS)
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid payload: options must be 1 to {} characters",
+                    MAX_POLL_OPTION_CHARS
+                ));
+            }
+            let distinct: HashSet<String> = poll.options.iter().map(|o| o.to_lowercase()).collect();
+            if distinct.len() != poll.options.len() {
+                return Err(anyhow::anyhow!("Invalid payload: options must be distinct"));
+            }
+            if poll.closes_at.is_some_and(|closes_at| closes_at <= Utc::now()) {
+                return Err(anyhow::anyhow!("Invalid payload: closes_at must be in the future"));
+            }
+
+            let text = text.or_else(|| Some(poll.question.clone()));
+            return Ok((text, Some(serde_json::to_value(poll)?)));
+        }
+        MessageType::System => {
+            let event: SystemEvent = parse_payload(draft)?;
+            serde_json::to_value(event)?
+        }
+    };
+
+    Ok((text, Some(payload)))
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(draft: &MessageDraft) -> Result<T> {
+    let payload = draft.payload.clone().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid payload: required for {} messages",
+            draft.message_type.as_str()
+        )
+    })?;
+
+    serde_json::from_value(payload).map_err(|e| anyhow::anyhow!("Invalid payload: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_cursor_round_trip() {
+        let cursor = SearchCursor {
+            rank: 0.0607927,
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(SearchCursor::parse(&cursor.encode()).unwrap(), cursor);
+
+        assert!(SearchCursor::parse("").is_err());
+        assert!(SearchCursor::parse("0.5_notatime_x").is_err());
+    }
+
+    fn draft(message_type: MessageType, payload: serde_json::Value) -> MessageDraft {
+        MessageDraft {
+            message_type,
+            text: None,
+            image_url: None,
+            payload: Some(payload),
+            reply_to_id: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_content() {
+        let pin = draft(
+            MessageType::Location,
+            serde_json::json!({ "latitude": 40.0, "longitude": -105.0, "label": "  Trailhead " }),
+        );
+        let (_, payload) = validate_content(&pin).unwrap();
+        assert_eq!(payload.unwrap()["label"], "Trailhead");
+
+        let off_map = draft(
+            MessageType::Location,
+            serde_json::json!({ "latitude": 91.0, "longitude": 0.0 }),
+        );
+        assert!(validate_content(&off_map).is_err());
+
+        let poll = draft(
+            MessageType::Poll,
+            serde_json::json!({ "question": "Lunch?", "options": ["Diner", "Tacos"] }),
+        );
+        let (text, _) = validate_content(&poll).unwrap();
+        assert_eq!(text.as_deref(), Some("Lunch?"));
+
+        let duplicate = draft(
+            MessageType::Poll,
+            serde_json::json!({ "question": "Lunch?", "options": ["Tacos", "tacos"] }),
+        );
+        assert!(validate_content(&duplicate).is_err());
+
+        let text_with_payload = MessageDraft {
+            text: Some("hi".to_string()),
+            ..draft(MessageType::Text, serde_json::json!({}))
+        };
+        assert!(validate_content(&text_with_payload).is_err());
+    }
+}
@@ -4,9 +4,23 @@ pub mod message_service;
 pub mod location_service;
 pub mod websocket;
 pub mod voice_call_signaling;
+pub mod itinerary_service;
+pub mod trip_stats_service;
+pub mod trail_service;
+pub mod retention_service;
+pub mod sos_service;
+pub mod poll_service;
+pub mod permissions;
 
 pub use auth_service::AuthService;
 pub use room_service::RoomService;
 pub use message_service::MessageService;
 pub use location_service::LocationService;
+pub use itinerary_service::ItineraryService;
+pub use trip_stats_service::TripStatsService;
+pub use trail_service::TrailService;
+pub use retention_service::RetentionService;
+pub use sos_service::SosService;
+pub use poll_service::PollService;
+pub use voice_call_signaling::VoiceCallSignalingService;
 pub use websocket::{WebSocketService, websocket_handler};
\ No newline at end of file
@@ -0,0 +1,79 @@
+use crate::entities::room_member::RoomRole;
+
+/// Room actions restricted by role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    RenameRoom,
+    ArchiveRoom,
+    EditItinerary,
+    ManageConvoy,
+    ManageInvites,
+    ApproveMembers,
+    KickMembers,
+    EndCalls,
+    DeleteOthersMessages,
+    PinMessages,
+    ManageRoles,
+    TransferOwnership,
+    DeleteRoom,
+}
+
+impl Permission {
+    /// Least privileged role allowed to perform the action
+    pub fn min_role(&self) -> RoomRole {
+        match self {
+            Permission::RenameRoom
+            | Permission::ArchiveRoom
+            | Permission::EditItinerary
+            | Permission::ManageConvoy
+            | Permission::ApproveMembers
+            | Permission::KickMembers
+            | Permission::EndCalls
+            | Permission::DeleteOthersMessages
+            | Permission::PinMessages => RoomRole::Admin,
+            Permission::ManageInvites
+            | Permission::ManageRoles
+            | Permission::TransferOwnership
+            | Permission::DeleteRoom => RoomRole::Owner,
+        }
+    }
+
+    pub fn allows(&self, role: RoomRole) -> bool {
+        role >= self.min_role()
+    }
+
+    /// Phrase used in permission errors, e.g. "edit the itinerary"
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Permission::RenameRoom => "edit room settings",
+            Permission::ArchiveRoom => "archive the room",
+            Permission::EditItinerary => "edit the itinerary",
+            Permission::ManageConvoy => "change convoy settings",
+            Permission::ManageInvites => "manage invite codes",
+            Permission::ApproveMembers => "review join requests",
+            Permission::KickMembers => "remove members",
+            Permission::EndCalls => "end other members' calls",
+            Permission::DeleteOthersMessages => "delete other members' messages",
+            Permission::PinMessages => "pin messages",
+            Permission::ManageRoles => "change member roles",
+            Permission::TransferOwnership => "transfer ownership",
+            Permission::DeleteRoom => "delete the room",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_allows() {
+        assert!(Permission::EditItinerary.allows(RoomRole::Admin));
+        assert!(Permission::EditItinerary.allows(RoomRole::Owner));
+        assert!(!Permission::EditItinerary.allows(RoomRole::Member));
+        assert!(!Permission::ManageRoles.allows(RoomRole::Admin));
+        assert!(!Permission::ManageInvites.allows(RoomRole::Admin));
+        assert!(Permission::TransferOwnership.allows(RoomRole::Owner));
+        assert!(!Permission::DeleteRoom.allows(RoomRole::Admin));
+    }
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use crate::entities::message::{MessageType, PollPayload};
+use crate::entities::{message, poll_vote, room};
+use crate::services::room_service::ensure_room_active;
+
+/// Current standing of a poll as seen by one member
+#[derive(Debug, Clone, Serialize)]
+pub struct PollResults {
+    pub message_id: Uuid,
+    /// Votes per option, in option order
+    pub tallies: Vec<u64>,
+    /// Members who voted for at least one option
+    pub total_voters: u64,
+    /// Options the viewing member chose
+    pub my_votes: Vec<usize>,
+    pub multiple_choice: bool,
+    pub closes_at: Option<DateTime<Utc>>,
+    /// Past `closes_at`, or the room is archived
+    pub closed: bool,
+}
+
+pub struct PollService {
+    db: DatabaseConnection,
+}
+
+impl PollService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Replaces the user's vote on a poll with `options`; an empty list
+    /// withdraws it. Fails once the poll has closed or the room is archived.
+    pub async fn vote(
+        &self,
+        room_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<PollResults> {
+        let txn = self.db.begin().await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        // Lock the poll so concurrent votes by the same member apply one at a time
+        let message = message::Entity::find_by_id(message_id)
+            .filter(message::Column::RoomId.eq(room_id))
+            .filter(message::Column::MessageType.eq(MessageType::Poll.as_str()))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Poll not found"))?;
+        let poll = poll_payload(&message)?;
+
+        if is_closed(&poll) {
+            return Err(anyhow::anyhow!("Poll is closed"));
+        }
+
+        let options: BTreeSet<usize> = options.into_iter().collect();
+        if options.iter().any(|&i| i >= poll.options.len()) {
+            return Err(anyhow::anyhow!("Invalid options: no such option in this poll"));
+        }
+        if !poll.multiple_choice && options.len() > 1 {
+            return Err(anyhow::anyhow!("Invalid options: this poll allows a single choice"));
+        }
+
+        poll_vote::Entity::delete_many()
+            .filter(poll_vote::Column::MessageId.eq(message.id))
+            .filter(poll_vote::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        if !options.is_empty() {
+            let now = Utc::now();
+            let votes = options.into_iter().map(|option_index| poll_vote::ActiveModel {
+                message_id: Set(message.id),
+                user_id: Set(user_id),
+                option_index: Set(option_index as i32),
+                created_at: Set(now),
+            });
+            poll_vote::Entity::insert_many(votes)
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        let votes = poll_vote::Entity::find()
+            .filter(poll_vote::Column::MessageId.eq(message.id))
+            .all(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(tally(message.id, &poll, &votes, user_id, false))
+    }
+
+    /// Results for every poll among `messages`, keyed by message id
+    pub async fn get_results(
+        &self,
+        messages: &[message::Model],
+        viewer_id: Uuid,
+    ) -> Result<HashMap<Uuid, PollResults>> {
+        let polls: Vec<(Uuid, Uuid, PollPayload)> = messages
+            .iter()
+            .filter(|m| m.message_type == MessageType::Poll.as_str())
+            .filter_map(|m| poll_payload(m).ok().map(|poll| (m.id, m.room_id, poll)))
+            .collect();
+        if polls.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let room_ids: BTreeSet<Uuid> = polls.iter().map(|(_, room_id, _)| *room_id).collect();
+        let archived: BTreeSet<Uuid> = room::Entity::find()
+            .filter(room::Column::Id.is_in(room_ids))
+            .filter(room::Column::IsActive.eq(false))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|room| room.id)
+            .collect();
+
+        let ids: Vec<Uuid> = polls.iter().map(|(id, _, _)| *id).collect();
+        let mut votes: HashMap<Uuid, Vec<poll_vote::Model>> = HashMap::new();
+        for vote in poll_vote::Entity::find()
+            .filter(poll_vote::Column::MessageId.is_in(ids))
+            .all(&self.db)
+            .await?
+        {
+            votes.entry(vote.message_id).or_default().push(vote);
+        }
+
+        Ok(polls
+            .into_iter()
+            .map(|(id, room_id, poll)| {
+                let votes = votes.get(&id).map(Vec::as_slice).unwrap_or_default();
+                let archived = archived.contains(&room_id);
+                (id, tally(id, &poll, votes, viewer_id, archived))
+            })
+            .collect())
+    }
+}
+
+fn poll_payload(message: &message::Model) -> Result<PollPayload> {
+    let payload = message
+        .payload
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("Poll has no payload"))?;
+    Ok(serde_json::from_value(payload)?)
+}
+
+fn is_closed(poll: &PollPayload) -> bool {
+    poll.closes_at.is_some_and(|closes_at| closes_at <= Utc::now())
+}
+
+/// Counts votes per option, ignoring any that point past the option list.
+/// Polls in archived rooms are reported as closed.
+fn tally(
+    message_id: Uuid,
+    poll: &PollPayload,
+    votes: &[poll_vote::Model],
+    viewer_id: Uuid,
+    room_archived: bool,
+) -> PollResults {
+    let mut tallies = vec![0; poll.options.len()];
+    let mut voters = BTreeSet::new();
+    let mut my_votes = Vec::new();
+
+    for vote in votes {
+        let Some(count) = usize::try_from(vote.option_index)
+            .ok()
+            .and_then(|i| tallies.get_mut(i))
+        else {
+            continue;
+        };
+        *count += 1;
+        voters.insert(vote.user_id);
+        if vote.user_id == viewer_id {
+            my_votes.push(vote.option_index as usize);
+        }
+    }
+    my_votes.sort_unstable();
+
+    PollResults {
+        message_id,
+        tallies,
+        total_voters: voters.len() as u64,
+        my_votes,
+        multiple_choice: poll.multiple_choice,
+        closes_at: poll.closes_at,
+        closed: room_archived || is_closed(poll),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(message_id: Uuid, user_id: Uuid, option_index: i32) -> poll_vote::Model {
+        poll_vote::Model {
+            message_id,
+            user_id,
+            option_index,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tally() {
+        let poll = PollPayload {
+            question: "Lunch?".to_string(),
+            options: vec!["Diner".to_string(), "Tacos".to_string(), "Pizza".to_string()],
+            multiple_choice: true,
+            closes_at: None,
+        };
+        let (message_id, me, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let votes = [
+            vote(message_id, me, 2),
+            vote(message_id, me, 0),
+            vote(message_id, other, 2),
+            vote(message_id, other, 7),
+        ];
+
+        let results = tally(message_id, &poll, &votes, me, false);
+
+        assert_eq!(results.tallies, vec![1, 0, 2]);
+        assert_eq!(results.total_voters, 2);
+        assert_eq!(results.my_votes, vec![0, 2]);
+        assert!(!results.closed);
+
+        assert!(tally(message_id, &poll, &votes, me, true).closed);
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
+    Statement, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::config::LocationConfig;
+use crate::entities::{location, sos_alert, stop_event, trail_compaction};
+use crate::services::TripStatsService;
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct RoomPurgeReport {
+    pub room_id: Uuid,
+    pub rows: i64,
+}
+
+pub struct RetentionService {
+    db: DatabaseConnection,
+    config: LocationConfig,
+    trip_stats: TripStatsService,
+}
+
+impl RetentionService {
+    pub fn new(db: DatabaseConnection, config: LocationConfig) -> Self {
+        Self {
+            trip_stats: TripStatsService::new(db.clone()),
+            db,
+            config,
+        }
+    }
+
+    /// Deletes the location history of rooms archived more than the
+    /// retention period ago, caching each room's trip summary first so the
+    /// statistics outlive the raw fixes. Stop events go with them and SOS
+    /// alerts lose their coordinates. In dry-run mode nothing is deleted and
+    /// the report lists what would be purged.
+    pub async fn purge_archived_locations(&self) -> Result<Vec<RoomPurgeReport>> {
+        let cutoff = Utc::now() - Duration::days(self.config.retention_days_after_archive);
+
+        let pending = RoomPurgeReport::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT r.id AS room_id, COUNT(l.id) AS rows
+            FROM rooms r
+            JOIN locations l ON l.room_id = r.id
+            WHERE r.is_active = false AND r.archived_at < $1
+            GROUP BY r.id
+            "#,
+            [cutoff.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        if self.config.retention_dry_run {
+            return Ok(pending);
+        }
+
+        let mut reports = Vec::with_capacity(pending.len());
+        for room in pending {
+            let rows = self.purge_room(room.room_id).await?;
+            reports.push(RoomPurgeReport {
+                room_id: room.room_id,
+                rows: rows as i64,
+            });
+        }
+
+        Ok(reports)
+    }
+
+    async fn purge_room(&self, room_id: Uuid) -> Result<u64> {
+        self.trip_stats.get_summary(room_id).await?;
+
+        let txn = self.db.begin().await?;
+
+        let result = location::Entity::delete_many()
+            .filter(location::Column::RoomId.eq(room_id))
+            .exec(&txn)
+            .await?;
+        trail_compaction::Entity::delete_many()
+            .filter(trail_compaction::Column::RoomId.eq(room_id))
+            .exec(&txn)
+            .await?;
+        stop_event::Entity::delete_many()
+            .filter(stop_event::Column::RoomId.eq(room_id))
+            .exec(&txn)
+            .await?;
+        sos_alert::Entity::update_many()
+            .col_expr(sos_alert::Column::Latitude, Expr::value(Option::<f64>::None))
+            .col_expr(sos_alert::Column::Longitude, Expr::value(Option::<f64>::None))
+            .col_expr(sos_alert::Column::LocatedAt, Expr::value(Option::<DateTime<Utc>>::None))
+            .filter(sos_alert::Column::RoomId.eq(room_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -1,25 +1,56 @@
 use anyhow::Result;
-use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
 use uuid::Uuid;
 
-use crate::entities::{room, room_member, user};
+use crate::config::RoomConfig;
+use crate::entities::{join_request, room, room_ban, room_invite, room_member, trip_summary, user};
+use crate::entities::join_request::JoinRequestStatus;
+use crate::entities::room_member::RoomRole;
+use crate::utils::invite_code::{generate_invite_code, normalize_invite_code};
+
+/// Attempts at finding an unused invite code before giving up
+const INVITE_CODE_ATTEMPTS: usize = 5;
+
+/// Result of redeeming an invite code
+pub enum JoinOutcome {
+    Joined(room::Model),
+    /// The room requires approval; an admin has to accept the request
+    Requested(room::Model, join_request::Model),
+}
 
 pub struct RoomService {
     db: DatabaseConnection,
+    config: RoomConfig,
 }
 
 impl RoomService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, config: RoomConfig) -> Self {
+        Self { db, config }
     }
 
+    /// Creates a room owned by `created_by`. Fails once the user already
+    /// owns the configured number of active rooms.
     pub async fn create_room(
         &self,
         name: String,
         description: Option<String>,
         created_by: Uuid,
     ) -> Result<room::Model> {
+        let txn = self.db.begin().await?;
+
+        let owned = owned_active_rooms(&txn, created_by).await?;
+        if owned >= self.config.max_active_rooms_per_user {
+            return Err(anyhow::anyhow!(
+                "Limit exceeded: you already own {} active rooms; archive one to create another",
+                owned
+            ));
+        }
+
         let new_room = room::ActiveModel {
             id: Set(Uuid::new_v4()),
             name: Set(name),
@@ -28,21 +59,36 @@ impl RoomService {
             created_at: Set(Utc::now()),
             updated_at: Set(Utc::now()),
             is_active: Set(true),
+            approval_required: Set(false),
+            max_members: Set(None),
+            max_spread_meters: Set(None),
+            convoy_leader_id: Set(None),
+            archived_at: Set(None),
         };
+        let room = new_room.insert(&txn).await?;
 
-        let room = new_room.insert(&self.db).await?;
-
-        // Add creator as member
-        self.join_room(room.id, created_by).await?;
+        // Add creator as the room's owner
+        self.add_member(&txn, room.id, created_by, RoomRole::Owner).await?;
 
+        txn.commit().await?;
         Ok(room)
     }
 
-    pub async fn get_user_rooms(&self, user_id: Uuid) -> Result<Vec<room::Model>> {
-        let rooms = room::Entity::find()
+    /// Rooms the user belongs to, most recently updated first. Archived
+    /// rooms are left out unless `include_archived` is set.
+    pub async fn get_user_rooms(
+        &self,
+        user_id: Uuid,
+        include_archived: bool,
+    ) -> Result<Vec<room::Model>> {
+        let mut select = room::Entity::find()
             .inner_join(room_member::Entity)
-            .filter(room_member::Column::UserId.eq(user_id))
-            .filter(room::Column::IsActive.eq(true))
+            .filter(room_member::Column::UserId.eq(user_id));
+        if !include_archived {
+            select = select.filter(room::Column::IsActive.eq(true));
+        }
+
+        let rooms = select
             .order_by_desc(room::Column::UpdatedAt)
             .all(&self.db)
             .await?;
@@ -57,52 +103,230 @@ impl RoomService {
             .ok_or_else(|| anyhow::anyhow!("Room not found"))
     }
 
-    pub async fn join_room(&self, room_id: Uuid, user_id: Uuid) -> Result<room_member::Model> {
-        // Check if room exists
-        self.get_room_by_id(room_id).await?;
+    /// Updates the room's name and description. An empty description
+    /// clears it.
+    pub async fn update_room(
+        &self,
+        room_id: Uuid,
+        name: Option<String>,
+        description: Option<String>,
+        approval_required: Option<bool>,
+        max_members: Option<Option<i32>>,
+    ) -> Result<room::Model> {
+        let room = self.get_room_by_id(room_id).await?;
+        if !room.is_active {
+            return Err(anyhow::anyhow!("Room is archived"));
+        }
 
-        // Check if already a member
-        let existing = room_member::Entity::find()
-            .filter(room_member::Column::RoomId.eq(room_id))
-            .filter(room_member::Column::UserId.eq(user_id))
-            .one(&self.db)
-            .await?;
+        let mut room: room::ActiveModel = room.into();
+        if let Some(name) = name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(anyhow::anyhow!("Invalid name: must not be empty"));
+            }
+            room.name = Set(name.to_string());
+        }
+        if let Some(description) = description {
+            let description = description.trim();
+            room.description = Set((!description.is_empty()).then(|| description.to_string()));
+        }
+        if let Some(approval_required) = approval_required {
+            room.approval_required = Set(approval_required);
+        }
+        if let Some(max_members) = max_members {
+            if let Some(limit) = max_members {
+                if limit < 1 || limit as u64 > self.config.max_members {
+                    return Err(anyhow::anyhow!(
+                        "Invalid max_members: must be between 1 and {}",
+                        self.config.max_members
+                    ));
+                }
+            }
+            room.max_members = Set(max_members);
+        }
+        room.updated_at = Set(Utc::now());
 
-        if existing.is_some() {
-            return existing.ok_or_else(|| anyhow::anyhow!("Already a member"));
+        Ok(room.update(&self.db).await?)
+    }
+
+    /// Archives or reactivates the room. Archiving stamps `archived_at`,
+    /// which location retention counts from, so repeating the current state
+    /// is a no-op. Reactivating fails once the owner already owns the
+    /// configured number of active rooms.
+    pub async fn set_archived(&self, room_id: Uuid, archived: bool) -> Result<room::Model> {
+        let txn = self.db.begin().await?;
+
+        let room = room::Entity::find_by_id(room_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        if room.is_active != archived {
+            return Ok(room);
         }
 
-        let new_member = room_member::ActiveModel {
-            id: Set(Uuid::new_v4()),
-            room_id: Set(room_id),
-            user_id: Set(user_id),
-            joined_at: Set(Utc::now()),
-        };
+        if !archived {
+            let owner = room_member::Entity::find()
+                .filter(room_member::Column::RoomId.eq(room_id))
+                .filter(room_member::Column::Role.eq(RoomRole::Owner.as_str()))
+                .one(&txn)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Room owner not found"))?;
+            let owned = owned_active_rooms(&txn, owner.user_id).await?;
+            if owned >= self.config.max_active_rooms_per_user {
+                return Err(anyhow::anyhow!(
+                    "Limit exceeded: the owner already owns {} active rooms; archive one to unarchive this room",
+                    owned
+                ));
+            }
+        }
 
-        let member = new_member.insert(&self.db).await?;
-        Ok(member)
+        let now = Utc::now();
+        let mut room: room::ActiveModel = room.into();
+        room.is_active = Set(!archived);
+        room.archived_at = Set(archived.then_some(now));
+        room.updated_at = Set(now);
+        let room = room.update(&txn).await?;
+
+        // The cached summary is stale once the trip continues
+        if !archived {
+            trip_summary::Entity::delete_by_id(room_id).exec(&txn).await?;
+        }
+
+        txn.commit().await?;
+        Ok(room)
+    }
+
+    /// Permanently deletes the room; everything belonging to it goes with it
+    pub async fn delete_room(&self, room_id: Uuid) -> Result<()> {
+        let result = room::Entity::delete_by_id(room_id).exec(&self.db).await?;
+        if result.rows_affected == 0 {
+            return Err(anyhow::anyhow!("Room not found"));
+        }
+
+        Ok(())
     }
 
-    pub async fn get_room_members(&self, room_id: Uuid) -> Result<Vec<user::Model>> {
+    /// Members of the room with their membership record, in join order
+    pub async fn get_room_members(
+        &self,
+        room_id: Uuid,
+    ) -> Result<Vec<(room_member::Model, user::Model)>> {
         // Verify room exists
         self.get_room_by_id(room_id).await?;
 
-        let members = user::Entity::find()
-            .inner_join(room_member::Entity)
+        let members = room_member::Entity::find()
+            .find_also_related(user::Entity)
             .filter(room_member::Column::RoomId.eq(room_id))
+            .order_by_asc(room_member::Column::JoinedAt)
             .all(&self.db)
-            .await?;
+            .await?
+            .into_iter()
+            .filter_map(|(member, user)| user.map(|user| (member, user)))
+            .collect();
 
         Ok(members)
     }
 
+    /// Removes the user from the room. The owner has to hand over ownership
+    /// before they can leave.
     pub async fn leave_room(&self, room_id: Uuid, user_id: Uuid) -> Result<()> {
-        room_member::Entity::delete_many()
-            .filter(room_member::Column::RoomId.eq(room_id))
-            .filter(room_member::Column::UserId.eq(user_id))
+        let txn = self.db.begin().await?;
+
+        let member = find_member(&txn, room_id, user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Member not found"))?;
+        if member.room_role() == RoomRole::Owner {
+            return Err(anyhow::anyhow!(
+                "Invalid request: the owner must transfer ownership before leaving"
+            ));
+        }
+
+        remove_membership(&txn, room_id, user_id).await?;
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Removes another member from the room, optionally banning them from
+    /// rejoining. Only members ranked below the actor can be removed.
+    pub async fn kick_member(
+        &self,
+        room_id: Uuid,
+        actor_id: Uuid,
+        actor_role: RoomRole,
+        user_id: Uuid,
+        ban: bool,
+        reason: Option<String>,
+    ) -> Result<()> {
+        if actor_id == user_id {
+            return Err(anyhow::anyhow!("Invalid member: leave the room instead of removing yourself"));
+        }
+
+        let txn = self.db.begin().await?;
+
+        let member = find_member(&txn, room_id, user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Member not found"))?;
+        if member.room_role() >= actor_role {
+            return Err(anyhow::anyhow!(
+                "Forbidden: you can only remove members ranked below you"
+            ));
+        }
+
+        remove_membership(&txn, room_id, user_id).await?;
+
+        if ban {
+            let ban = room_ban::ActiveModel {
+                room_id: Set(room_id),
+                user_id: Set(user_id),
+                banned_by: Set(actor_id),
+                reason: Set(reason),
+                created_at: Set(Utc::now()),
+            };
+            room_ban::Entity::insert(ban)
+                .on_conflict(
+                    OnConflict::columns([room_ban::Column::RoomId, room_ban::Column::UserId])
+                        .update_columns([
+                            room_ban::Column::BannedBy,
+                            room_ban::Column::Reason,
+                            room_ban::Column::CreatedAt,
+                        ])
+                        .to_owned(),
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Users banned from the room with their user record, newest first
+    pub async fn get_bans(&self, room_id: Uuid) -> Result<Vec<(room_ban::Model, user::Model)>> {
+        let bans = room_ban::Entity::find()
+            .find_also_related(user::Entity)
+            .filter(room_ban::Column::RoomId.eq(room_id))
+            .order_by_desc(room_ban::Column::CreatedAt)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter_map(|(ban, user)| user.map(|user| (ban, user)))
+            .collect();
+
+        Ok(bans)
+    }
+
+    /// Lifts a ban so the user can rejoin with an invite code
+    pub async fn unban(&self, room_id: Uuid, user_id: Uuid) -> Result<()> {
+        let result = room_ban::Entity::delete_by_id((room_id, user_id))
             .exec(&self.db)
             .await?;
 
+        if result.rows_affected == 0 {
+            return Err(anyhow::anyhow!("Ban not found"));
+        }
+
         Ok(())
     }
 
@@ -115,4 +339,517 @@ impl RoomService {
 
         Ok(member.is_some())
     }
+
+    pub async fn update_convoy_settings(
+        &self,
+        room_id: Uuid,
+        max_spread_meters: Option<f64>,
+        convoy_leader_id: Option<Uuid>,
+    ) -> Result<room::Model> {
+        if let Some(spread) = max_spread_meters {
+            if !spread.is_finite() || spread <= 0.0 {
+                return Err(anyhow::anyhow!("Invalid max spread: must be a positive distance"));
+            }
+        }
+
+        if let Some(leader_id) = convoy_leader_id {
+            if !self.is_member(room_id, leader_id).await? {
+                return Err(anyhow::anyhow!("Invalid leader: user is not a member of this room"));
+            }
+        }
+
+        let room = self.get_room_by_id(room_id).await?;
+        if !room.is_active {
+            return Err(anyhow::anyhow!("Room is archived"));
+        }
+
+        let mut room: room::ActiveModel = room.into();
+        room.max_spread_meters = Set(max_spread_meters);
+        room.convoy_leader_id = Set(convoy_leader_id);
+        room.updated_at = Set(Utc::now());
+
+        let room = room.update(&self.db).await?;
+        Ok(room)
+    }
+
+    pub async fn get_member(&self, room_id: Uuid, user_id: Uuid) -> Result<Option<room_member::Model>> {
+        let member = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(room_member::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+
+        Ok(member)
+    }
+
+    /// The user's role in the room, or `None` if they are not a member
+    pub async fn get_role(&self, room_id: Uuid, user_id: Uuid) -> Result<Option<RoomRole>> {
+        let member = self.get_member(room_id, user_id).await?;
+        Ok(member.map(|m| m.room_role()))
+    }
+
+    /// Promotes a member to admin or demotes an admin to member. The owner's
+    /// role only changes through an ownership transfer.
+    pub async fn set_member_role(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+        role: RoomRole,
+    ) -> Result<room_member::Model> {
+        if role == RoomRole::Owner {
+            return Err(anyhow::anyhow!("Invalid role: transfer ownership to change the owner"));
+        }
+
+        let member = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(room_member::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Member not found"))?;
+
+        if member.room_role() == RoomRole::Owner {
+            return Err(anyhow::anyhow!("Invalid member: the owner's role can't be changed"));
+        }
+
+        let mut member: room_member::ActiveModel = member.into();
+        member.role = Set(role.as_str().to_string());
+        Ok(member.update(&self.db).await?)
+    }
+
+    /// Hands ownership to another member; the previous owner becomes an
+    /// admin. Returns the updated memberships, new owner first.
+    pub async fn transfer_ownership(
+        &self,
+        room_id: Uuid,
+        owner_id: Uuid,
+        new_owner_id: Uuid,
+    ) -> Result<Vec<room_member::Model>> {
+        if owner_id == new_owner_id {
+            return Err(anyhow::anyhow!("Invalid member: you already own this room"));
+        }
+
+        let txn = self.db.begin().await?;
+
+        let members = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(room_member::Column::UserId.is_in([owner_id, new_owner_id]))
+            .lock_exclusive()
+            .all(&txn)
+            .await?;
+
+        let owner = members
+            .iter()
+            .find(|m| m.user_id == owner_id && m.room_role() == RoomRole::Owner)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Forbidden: only the room owner can transfer ownership"))?;
+        let new_owner = members
+            .iter()
+            .find(|m| m.user_id == new_owner_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Member not found"))?;
+
+        // Archived rooms don't count towards the new owner's limit
+        let room = room::Entity::find_by_id(room_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        if room.is_active {
+            let owned = owned_active_rooms(&txn, new_owner_id).await?;
+            if owned >= self.config.max_active_rooms_per_user {
+                return Err(anyhow::anyhow!(
+                    "Limit exceeded: the new owner already owns {} active rooms",
+                    owned
+                ));
+            }
+        }
+
+        let mut new_owner: room_member::ActiveModel = new_owner.into();
+        new_owner.role = Set(RoomRole::Owner.as_str().to_string());
+        let new_owner = new_owner.update(&txn).await?;
+
+        let mut owner: room_member::ActiveModel = owner.into();
+        owner.role = Set(RoomRole::Admin.as_str().to_string());
+        let owner = owner.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(vec![new_owner, owner])
+    }
+
+    pub async fn create_invite(
+        &self,
+        room_id: Uuid,
+        created_by: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+        max_uses: Option<i32>,
+    ) -> Result<room_invite::Model> {
+        if max_uses.is_some_and(|n| n < 1) {
+            return Err(anyhow::anyhow!("Invalid max_uses: must be at least 1"));
+        }
+        let now = Utc::now();
+        if expires_at.is_some_and(|t| t <= now) {
+            return Err(anyhow::anyhow!("Invalid expires_at: must be in the future"));
+        }
+        self.get_room_by_id(room_id).await?;
+
+        for _ in 0..INVITE_CODE_ATTEMPTS {
+            let code = generate_invite_code();
+            let taken = room_invite::Entity::find()
+                .filter(room_invite::Column::Code.eq(code.as_str()))
+                .one(&self.db)
+                .await?
+                .is_some();
+            if taken {
+                continue;
+            }
+
+            let invite = room_invite::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                room_id: Set(room_id),
+                code: Set(code),
+                created_by: Set(created_by),
+                created_at: Set(now),
+                expires_at: Set(expires_at),
+                max_uses: Set(max_uses),
+                uses: Set(0),
+                revoked_at: Set(None),
+            };
+            return Ok(invite.insert(&self.db).await?);
+        }
+
+        Err(anyhow::anyhow!("Could not generate a unique invite code"))
+    }
+
+    /// Every invite created for the room, newest first
+    pub async fn get_invites(&self, room_id: Uuid) -> Result<Vec<room_invite::Model>> {
+        let invites = room_invite::Entity::find()
+            .filter(room_invite::Column::RoomId.eq(room_id))
+            .order_by_desc(room_invite::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(invites)
+    }
+
+    pub async fn revoke_invite(&self, room_id: Uuid, invite_id: Uuid) -> Result<room_invite::Model> {
+        let invite = room_invite::Entity::find_by_id(invite_id)
+            .filter(room_invite::Column::RoomId.eq(room_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invite not found"))?;
+
+        if invite.revoked_at.is_some() {
+            return Ok(invite);
+        }
+
+        let mut invite: room_invite::ActiveModel = invite.into();
+        invite.revoked_at = Set(Some(Utc::now()));
+        Ok(invite.update(&self.db).await?)
+    }
+
+    /// Redeems an invite code and adds the user to its room, or files a join
+    /// request when the room requires approval. Users who are already
+    /// members or already waiting don't consume a use.
+    pub async fn join_with_code(&self, code: &str, user_id: Uuid) -> Result<JoinOutcome> {
+        let code = normalize_invite_code(code);
+        let txn = self.db.begin().await?;
+
+        // Lock the invite so concurrent joins can't exceed max_uses
+        let invite = room_invite::Entity::find()
+            .filter(room_invite::Column::Code.eq(code.as_str()))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Invite code not found"))?;
+
+        let room = room::Entity::find_by_id(invite.room_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        if !room.is_active {
+            return Err(anyhow::anyhow!("Invalid invite code: the room is archived"));
+        }
+
+        let existing = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room.id))
+            .filter(room_member::Column::UserId.eq(user_id))
+            .one(&txn)
+            .await?;
+        if existing.is_some() {
+            return Ok(JoinOutcome::Joined(room));
+        }
+
+        let banned = room_ban::Entity::find_by_id((room.id, user_id))
+            .one(&txn)
+            .await?;
+        if banned.is_some() {
+            return Err(anyhow::anyhow!("You are banned from this room"));
+        }
+
+        if room.approval_required {
+            if let Some(pending) = find_pending_request(&txn, room.id, user_id).await? {
+                return Ok(JoinOutcome::Requested(room, pending));
+            }
+        }
+
+        if let Some(reason) = invite.unusable_reason(Utc::now()) {
+            return Err(anyhow::anyhow!("Invalid invite code: {}", reason));
+        }
+
+        let outcome = if room.approval_required {
+            let request = join_request::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                room_id: Set(room.id),
+                user_id: Set(user_id),
+                invite_id: Set(Some(invite.id)),
+                status: Set(JoinRequestStatus::Pending.as_str().to_string()),
+                created_at: Set(Utc::now()),
+                decided_by: Set(None),
+                decided_at: Set(None),
+            };
+            let inserted = join_request::Entity::insert(request)
+                .on_conflict(OnConflict::new().do_nothing().to_owned())
+                .exec_without_returning(&txn)
+                .await?;
+            let request = find_pending_request(&txn, room.id, user_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Join request not found"))?;
+
+            // A concurrent redemption filed the request first; this one
+            // doesn't use up the invite
+            if inserted == 0 {
+                return Ok(JoinOutcome::Requested(room, request));
+            }
+            JoinOutcome::Requested(room, request)
+        } else {
+            self.add_member(&txn, room.id, user_id, RoomRole::Member).await?;
+            JoinOutcome::Joined(room)
+        };
+
+        let uses = invite.uses + 1;
+        let mut invite: room_invite::ActiveModel = invite.into();
+        invite.uses = Set(uses);
+        invite.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(outcome)
+    }
+
+    /// Pending join requests with the requesting user, oldest first
+    pub async fn get_join_requests(
+        &self,
+        room_id: Uuid,
+    ) -> Result<Vec<(join_request::Model, user::Model)>> {
+        let requests = join_request::Entity::find()
+            .find_also_related(user::Entity)
+            .filter(join_request::Column::RoomId.eq(room_id))
+            .filter(join_request::Column::Status.eq(JoinRequestStatus::Pending.as_str()))
+            .order_by_asc(join_request::Column::CreatedAt)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter_map(|(request, user)| user.map(|user| (request, user)))
+            .collect();
+
+        Ok(requests)
+    }
+
+    /// Approves or denies a pending join request; approving adds the
+    /// requester as a member
+    pub async fn decide_join_request(
+        &self,
+        room_id: Uuid,
+        request_id: Uuid,
+        decided_by: Uuid,
+        approve: bool,
+    ) -> Result<join_request::Model> {
+        let txn = self.db.begin().await?;
+
+        let request = join_request::Entity::find_by_id(request_id)
+            .filter(join_request::Column::RoomId.eq(room_id))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Join request not found"))?;
+        if !request.is_pending() {
+            return Err(anyhow::anyhow!(
+                "Invalid request: the join request was already {}",
+                request.status
+            ));
+        }
+
+        let status = if approve {
+            ensure_room_active(&txn, room_id).await?;
+            let banned = room_ban::Entity::find_by_id((room_id, request.user_id))
+                .one(&txn)
+                .await?;
+            if banned.is_some() {
+                return Err(anyhow::anyhow!("The user is banned from this room"));
+            }
+            if find_member(&txn, room_id, request.user_id).await?.is_none() {
+                self.add_member(&txn, room_id, request.user_id, RoomRole::Member)
+                    .await?;
+            }
+            JoinRequestStatus::Approved
+        } else {
+            JoinRequestStatus::Denied
+        };
+
+        let mut request: join_request::ActiveModel = request.into();
+        request.status = Set(status.as_str().to_string());
+        request.decided_by = Set(Some(decided_by));
+        request.decided_at = Set(Some(Utc::now()));
+        let request = request.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(request)
+    }
+
+    /// Inserts a membership once the room has room for it. The room row is
+    /// locked so concurrent joins can't overshoot the limit.
+    async fn add_member<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        room_id: Uuid,
+        user_id: Uuid,
+        role: RoomRole,
+    ) -> Result<room_member::Model> {
+        let room = room::Entity::find_by_id(room_id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+
+        let limit = room
+            .max_members
+            .map(|limit| (limit.max(0) as u64).min(self.config.max_members))
+            .unwrap_or(self.config.max_members);
+        let members = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .count(db)
+            .await?;
+        if members >= limit {
+            return Err(anyhow::anyhow!(
+                "Limit exceeded: the room is full ({} members)",
+                limit
+            ));
+        }
+
+        let member = room_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            room_id: Set(room_id),
+            user_id: Set(user_id),
+            joined_at: Set(Utc::now()),
+            role: Set(role.as_str().to_string()),
+            last_read_message_id: Set(None),
+            last_read_at: Set(None),
+        }
+        .insert(db)
+        .await?;
+
+        Ok(member)
+    }
+
+    /// Users who can act on the room's admin-only events
+    pub async fn get_admin_ids(&self, room_id: Uuid) -> Result<Vec<Uuid>> {
+        let admins = room_member::Entity::find()
+            .filter(room_member::Column::RoomId.eq(room_id))
+            .filter(
+                room_member::Column::Role
+                    .is_in([RoomRole::Admin.as_str(), RoomRole::Owner.as_str()]),
+            )
+            .all(&self.db)
+            .await?;
+
+        Ok(admins.into_iter().map(|m| m.user_id).collect())
+    }
+
+    /// Shareable join link for a code, when a link base URL is configured
+    pub fn invite_link(&self, code: &str) -> Option<String> {
+        self.config
+            .invite_link_base_url
+            .as_ref()
+            .map(|base| format!("{}/{}", base.trim_end_matches('/'), code))
+    }
+}
+
+/// Fails with "Room is archived" once a room has become read-only
+pub async fn ensure_room_active<C: ConnectionTrait>(db: &C, room_id: Uuid) -> Result<()> {
+    let room = room::Entity::find_by_id(room_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+
+    if !room.is_active {
+        return Err(anyhow::anyhow!("Room is archived"));
+    }
+
+    Ok(())
+}
+
+async fn find_member<C: ConnectionTrait>(
+    db: &C,
+    room_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<room_member::Model>> {
+    let member = room_member::Entity::find()
+        .filter(room_member::Column::RoomId.eq(room_id))
+        .filter(room_member::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(db)
+        .await?;
+
+    Ok(member)
+}
+
+/// Number of active rooms the user owns. Locks the user so concurrent
+/// changes can't both pass the active room limit.
+async fn owned_active_rooms<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<u64> {
+    user::Entity::find_by_id(user_id)
+        .lock_exclusive()
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+    let owned = room::Entity::find()
+        .inner_join(room_member::Entity)
+        .filter(room_member::Column::UserId.eq(user_id))
+        .filter(room_member::Column::Role.eq(RoomRole::Owner.as_str()))
+        .filter(room::Column::IsActive.eq(true))
+        .count(db)
+        .await?;
+
+    Ok(owned)
+}
+
+async fn find_pending_request<C: ConnectionTrait>(
+    db: &C,
+    room_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<join_request::Model>> {
+    let request = join_request::Entity::find()
+        .filter(join_request::Column::RoomId.eq(room_id))
+        .filter(join_request::Column::UserId.eq(user_id))
+        .filter(join_request::Column::Status.eq(JoinRequestStatus::Pending.as_str()))
+        .one(db)
+        .await?;
+
+    Ok(request)
+}
+
+/// Deletes the membership and clears the convoy leader if it was the user
+async fn remove_membership<C: ConnectionTrait>(db: &C, room_id: Uuid, user_id: Uuid) -> Result<()> {
+    room_member::Entity::delete_many()
+        .filter(room_member::Column::RoomId.eq(room_id))
+        .filter(room_member::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    room::Entity::update_many()
+        .col_expr(room::Column::ConvoyLeaderId, Expr::value(Option::<Uuid>::None))
+        .filter(room::Column::Id.eq(room_id))
+        .filter(room::Column::ConvoyLeaderId.eq(user_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
 }
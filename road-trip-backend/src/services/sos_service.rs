@@ -0,0 +1,153 @@
+use anyhow::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::message::{MessageType, SystemEvent};
+use crate::entities::{location, message, message_pin, room, sos_alert};
+use crate::services::room_service::ensure_room_active;
+
+pub struct SosService {
+    db: DatabaseConnection,
+}
+
+impl SosService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Records an SOS with the sender's latest fix and posts a pinned system
+    /// message for it. The exact fix is used regardless of the sender's
+    /// location sharing settings.
+    pub async fn raise_sos(
+        &self,
+        room_id: Uuid,
+        user_id: Uuid,
+        sender_name: &str,
+        note: Option<String>,
+    ) -> Result<(sos_alert::Model, message::Model)> {
+        let note = note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let txn = self.db.begin().await?;
+        ensure_room_active(&txn, room_id).await?;
+
+        let fix = location::Entity::find()
+            .filter(location::Column::RoomId.eq(room_id))
+            .filter(location::Column::UserId.eq(user_id))
+            .order_by_desc(location::Column::Timestamp)
+            .one(&txn)
+            .await?;
+
+        let now = Utc::now();
+        let sos_id = Uuid::new_v4();
+        let text = match &note {
+            Some(note) => format!("SOS from {}: {}", sender_name, note),
+            None => format!("SOS from {}", sender_name),
+        };
+        let message = message::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            room_id: Set(room_id),
+            user_id: Set(user_id),
+            text: Set(Some(text)),
+            image_url: Set(None),
+            message_type: Set(MessageType::System.as_str().to_string()),
+            created_at: Set(now),
+            reply_to_id: Set(None),
+            payload: Set(Some(serde_json::to_value(SystemEvent::Sos { user_id, sos_id })?)),
+        }
+        .insert(&txn)
+        .await?;
+
+        message_pin::ActiveModel {
+            message_id: Set(message.id),
+            room_id: Set(room_id),
+            pinned_by: Set(None),
+            pinned_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        let alert = sos_alert::ActiveModel {
+            id: Set(sos_id),
+            room_id: Set(room_id),
+            user_id: Set(user_id),
+            note: Set(note),
+            latitude: Set(fix.as_ref().map(|f| f.latitude)),
+            longitude: Set(fix.as_ref().map(|f| f.longitude)),
+            located_at: Set(fix.as_ref().map(|f| f.timestamp)),
+            message_id: Set(Some(message.id)),
+            created_at: Set(now),
+            acknowledged_by: Set(None),
+            acknowledged_at: Set(None),
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        Ok((alert, message))
+    }
+
+    /// Marks an alert as handled and unpins its chat message. Acknowledging
+    /// an alert twice keeps the first acknowledgement.
+    pub async fn acknowledge(
+        &self,
+        room_id: Uuid,
+        sos_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<sos_alert::Model> {
+        let txn = self.db.begin().await?;
+
+        let alert = sos_alert::Entity::find_by_id(sos_id)
+            .filter(sos_alert::Column::RoomId.eq(room_id))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("SOS alert not found"))?;
+
+        if alert.acknowledged_at.is_some() {
+            return Ok(alert);
+        }
+
+        if let Some(message_id) = alert.message_id {
+            message_pin::Entity::delete_by_id(message_id).exec(&txn).await?;
+        }
+
+        let mut active: sos_alert::ActiveModel = alert.into();
+        active.acknowledged_by = Set(Some(user_id));
+        active.acknowledged_at = Set(Some(Utc::now()));
+        let alert = active.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(alert)
+    }
+
+    /// Unacknowledged alerts in a room, oldest first
+    pub async fn get_active_alerts(&self, room_id: Uuid) -> Result<Vec<sos_alert::Model>> {
+        let alerts = sos_alert::Entity::find()
+            .filter(sos_alert::Column::RoomId.eq(room_id))
+            .filter(sos_alert::Column::AcknowledgedAt.is_null())
+            .order_by_asc(sos_alert::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(alerts)
+    }
+
+    /// Unacknowledged alerts across all rooms that are not archived, oldest first
+    pub async fn get_all_active_alerts(&self) -> Result<Vec<sos_alert::Model>> {
+        let alerts = sos_alert::Entity::find()
+            .inner_join(room::Entity)
+            .filter(room::Column::IsActive.eq(true))
+            .filter(sos_alert::Column::AcknowledgedAt.is_null())
+            .order_by_asc(sos_alert::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(alerts)
+    }
+}
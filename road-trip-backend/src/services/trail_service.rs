@@ -0,0 +1,225 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, Set, Statement, TransactionTrait,
+};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+use crate::config::LocationConfig;
+use crate::entities::{location, trail_compaction};
+use crate::services::location_service::{apply_sharing_settings, by_current_members};
+use crate::services::trip_stats_service::stop_cluster_ranges;
+use crate::utils::geo::douglas_peucker;
+
+/// Upper bound on tolerance doubling when shrinking a track to `max_points`
+const MAX_SIMPLIFY_PASSES: usize = 24;
+
+#[derive(Debug, Clone, Default)]
+pub struct TrailQuery {
+    pub user_id: Option<Uuid>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub tolerance_m: Option<f64>,
+    pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberTrack {
+    pub user_id: Uuid,
+    pub original_points: usize,
+    pub points: Vec<location::Model>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PendingTrail {
+    room_id: Uuid,
+    user_id: Uuid,
+    compacted_until: Option<DateTime<Utc>>,
+}
+
+pub struct TrailService {
+    db: DatabaseConnection,
+    config: LocationConfig,
+}
+
+impl TrailService {
+    pub fn new(db: DatabaseConnection, config: LocationConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Location history for a room grouped into one track per member,
+    /// optionally simplified for rendering. Members' sharing settings are
+    /// applied for `viewer_id`.
+    pub async fn get_history(
+        &self,
+        room_id: Uuid,
+        viewer_id: Uuid,
+        query: TrailQuery,
+    ) -> Result<Vec<MemberTrack>> {
+        if query.tolerance_m.is_some_and(|t| !t.is_finite() || t < 0.0) {
+            return Err(anyhow::anyhow!("Invalid tolerance_m: must be a non-negative distance"));
+        }
+        if query.max_points.is_some_and(|n| n < 2) {
+            return Err(anyhow::anyhow!("Invalid max_points: must be at least 2"));
+        }
+
+        let mut select = location::Entity::find()
+            .filter(location::Column::RoomId.eq(room_id))
+            .filter(by_current_members(room_id));
+        if let Some(user_id) = query.user_id {
+            select = select.filter(location::Column::UserId.eq(user_id));
+        }
+        if let Some(since) = query.since {
+            select = select.filter(location::Column::Timestamp.gte(since));
+        }
+        if let Some(until) = query.until {
+            select = select.filter(location::Column::Timestamp.lte(until));
+        }
+
+        let fixes = select
+            .order_by_asc(location::Column::UserId)
+            .order_by_asc(location::Column::Timestamp)
+            .all(&self.db)
+            .await?;
+        let fixes = apply_sharing_settings(&self.db, room_id, Some(viewer_id), fixes).await?;
+
+        let tracks = fixes
+            .chunk_by(|a, b| a.user_id == b.user_id)
+            .map(|trail| MemberTrack {
+                user_id: trail[0].user_id,
+                original_points: trail.len(),
+                points: simplify_trail(trail, query.tolerance_m, query.max_points),
+            })
+            .collect();
+
+        Ok(tracks)
+    }
+
+    /// Decimates fixes older than the configured age with Douglas–Peucker,
+    /// keeping the first and last fix of every detected stop so stop
+    /// clusters survive compaction. Returns the number of fixes removed.
+    pub async fn compact_old_trails(&self) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::hours(self.config.trail_compaction_age_hours);
+
+        let pending = PendingTrail::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT l.room_id, l.user_id, MAX(c.compacted_until) AS compacted_until
+            FROM locations l
+            LEFT JOIN trail_compactions c
+                ON c.room_id = l.room_id AND c.user_id = l.user_id
+            WHERE l.timestamp < $1
+                AND (c.compacted_until IS NULL OR l.timestamp > c.compacted_until)
+            GROUP BY l.room_id, l.user_id
+            "#,
+            [cutoff.into()],
+        ))
+        .all(&self.db)
+        .await?;
+
+        let mut removed = 0;
+        for trail in pending {
+            removed += self.compact_trail(trail, cutoff).await?;
+        }
+
+        Ok(removed)
+    }
+
+    async fn compact_trail(&self, trail: PendingTrail, cutoff: DateTime<Utc>) -> Result<u64> {
+        let txn = self.db.begin().await?;
+
+        let mut select = location::Entity::find()
+            .filter(location::Column::RoomId.eq(trail.room_id))
+            .filter(location::Column::UserId.eq(trail.user_id))
+            .filter(location::Column::Timestamp.lt(cutoff));
+        if let Some(compacted_until) = trail.compacted_until {
+            select = select.filter(location::Column::Timestamp.gt(compacted_until));
+        }
+        let fixes = select
+            .order_by_asc(location::Column::Timestamp)
+            .all(&txn)
+            .await?;
+
+        let points: Vec<(f64, f64)> = fixes.iter().map(|f| (f.latitude, f.longitude)).collect();
+        let mut keep: BTreeSet<usize> = douglas_peucker(
+            &points,
+            self.config.trail_compaction_tolerance_meters,
+        )
+        .into_iter()
+        .collect();
+        for range in stop_cluster_ranges(&fixes) {
+            keep.insert(range.start);
+            keep.insert(range.end - 1);
+        }
+
+        let drop_ids: Vec<Uuid> = fixes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !keep.contains(i))
+            .map(|(_, f)| f.id)
+            .collect();
+
+        let mut removed = 0;
+        for chunk in drop_ids.chunks(1000) {
+            let result = location::Entity::delete_many()
+                .filter(location::Column::Id.is_in(chunk.to_vec()))
+                .exec(&txn)
+                .await?;
+            removed += result.rows_affected;
+        }
+
+        let watermark = trail_compaction::ActiveModel {
+            room_id: Set(trail.room_id),
+            user_id: Set(trail.user_id),
+            compacted_until: Set(cutoff),
+            updated_at: Set(Utc::now()),
+        };
+        trail_compaction::Entity::insert(watermark)
+            .on_conflict(
+                OnConflict::columns([
+                    trail_compaction::Column::RoomId,
+                    trail_compaction::Column::UserId,
+                ])
+                .update_columns([
+                    trail_compaction::Column::CompactedUntil,
+                    trail_compaction::Column::UpdatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+
+        txn.commit().await?;
+        Ok(removed)
+    }
+}
+
+/// Applies Douglas–Peucker at `tolerance_m`, then keeps doubling the
+/// tolerance until the track fits in `max_points`.
+fn simplify_trail(
+    trail: &[location::Model],
+    tolerance_m: Option<f64>,
+    max_points: Option<usize>,
+) -> Vec<location::Model> {
+    if tolerance_m.is_none() && max_points.is_none() {
+        return trail.to_vec();
+    }
+
+    let points: Vec<(f64, f64)> = trail.iter().map(|f| (f.latitude, f.longitude)).collect();
+    let mut tolerance = tolerance_m.unwrap_or(0.0);
+    let mut indices = douglas_peucker(&points, tolerance);
+
+    if let Some(max_points) = max_points {
+        let mut passes = 0;
+        while indices.len() > max_points && passes < MAX_SIMPLIFY_PASSES {
+            tolerance = if tolerance > 0.0 { tolerance * 2.0 } else { 1.0 };
+            indices = douglas_peucker(&points, tolerance);
+            passes += 1;
+        }
+    }
+
+    indices.into_iter().map(|i| trail[i].clone()).collect()
+}
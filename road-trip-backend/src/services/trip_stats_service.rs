@@ -0,0 +1,342 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::{location, location_sharing, room, trip_summary};
+use crate::services::location_service::sharing_settings;
+use crate::utils::geo::{haversine_distance, snap_to_grid};
+
+/// Segments slower than this (m/s, ~3.6 km/h) count as stopped time and
+/// their distance is treated as GPS jitter
+const MOVING_SPEED_MPS: f64 = 1.0;
+
+/// Segments faster than this (m/s, ~360 km/h) are GPS glitches and ignored
+const MAX_PLAUSIBLE_SPEED_MPS: f64 = 100.0;
+
+/// Fixes staying within this radius of the first fix form a stop cluster...
+const STOP_CLUSTER_RADIUS_M: f64 = 100.0;
+
+/// ...provided they span at least this long
+const STOP_CLUSTER_MIN_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopCluster {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub arrived_at: DateTime<Utc>,
+    pub departed_at: DateTime<Utc>,
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberTripStats {
+    pub user_id: Uuid,
+    pub point_count: usize,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub total_distance_meters: f64,
+    pub moving_seconds: i64,
+    pub stopped_seconds: i64,
+    pub max_speed_mps: f64,
+    pub average_speed_mps: f64,
+    pub stops: Vec<StopCluster>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripSummary {
+    pub room_id: Uuid,
+    pub archived: bool,
+    pub computed_at: DateTime<Utc>,
+    pub members: Vec<MemberTripStats>,
+}
+
+pub struct TripStatsService {
+    db: DatabaseConnection,
+}
+
+impl TripStatsService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Returns the trip summary for a room. Active rooms are computed on every
+    /// call; once a room is archived the result is computed once and cached.
+    /// Members' sharing settings are applied to stop locations before the
+    /// summary is cached or returned.
+    pub async fn get_summary(&self, room_id: Uuid) -> Result<TripSummary> {
+        let room = room::Entity::find_by_id(room_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        let archived = !room.is_active;
+        let settings = sharing_settings(&self.db, room_id).await?;
+        let now = Utc::now();
+
+        if archived {
+            if let Some(cached) = trip_summary::Entity::find_by_id(room_id)
+                .one(&self.db)
+                .await?
+            {
+                let mut summary: TripSummary = serde_json::from_value(cached.summary)?;
+                share_stops(&mut summary, &settings, now);
+                return Ok(summary);
+            }
+        }
+
+        let mut summary = self.compute_summary(room_id, archived).await?;
+        share_stops(&mut summary, &settings, now);
+
+        if archived {
+            let cached = trip_summary::ActiveModel {
+                room_id: Set(room_id),
+                summary: Set(serde_json::to_value(&summary)?),
+                computed_at: Set(summary.computed_at),
+            };
+            trip_summary::Entity::insert(cached)
+                .on_conflict(
+                    OnConflict::column(trip_summary::Column::RoomId)
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .exec_without_returning(&self.db)
+                .await?;
+        }
+
+        Ok(summary)
+    }
+
+    async fn compute_summary(&self, room_id: Uuid, archived: bool) -> Result<TripSummary> {
+        let fixes = location::Entity::find()
+            .filter(location::Column::RoomId.eq(room_id))
+            .order_by_asc(location::Column::UserId)
+            .order_by_asc(location::Column::Timestamp)
+            .all(&self.db)
+            .await?;
+
+        let members = fixes
+            .chunk_by(|a, b| a.user_id == b.user_id)
+            .map(|trail| compute_member_stats(trail[0].user_id, trail))
+            .collect();
+
+        Ok(TripSummary {
+            room_id,
+            archived,
+            computed_at: Utc::now(),
+            members,
+        })
+    }
+}
+
+/// Derives distance, timing, speed and stop statistics from one member's
+/// chronologically ordered trail.
+pub fn compute_member_stats(user_id: Uuid, trail: &[location::Model]) -> MemberTripStats {
+    let mut total_distance_meters = 0.0;
+    let mut moving_seconds = 0.0;
+    let mut stopped_seconds = 0.0;
+    let mut max_speed_mps: f64 = 0.0;
+
+    for pair in trail.windows(2) {
+        let elapsed = (pair[1].timestamp - pair[0].timestamp).num_milliseconds() as f64 / 1000.0;
+        if elapsed <= 0.0 {
+            continue;
+        }
+
+        let distance = haversine_distance(
+            pair[0].latitude,
+            pair[0].longitude,
+            pair[1].latitude,
+            pair[1].longitude,
+        );
+        let speed = distance / elapsed;
+
+        if speed > MAX_PLAUSIBLE_SPEED_MPS {
+            continue;
+        }
+        if speed >= MOVING_SPEED_MPS {
+            total_distance_meters += distance;
+            moving_seconds += elapsed;
+            max_speed_mps = max_speed_mps.max(speed);
+        } else {
+            stopped_seconds += elapsed;
+        }
+    }
+
+    let average_speed_mps = if moving_seconds > 0.0 {
+        total_distance_meters / moving_seconds
+    } else {
+        0.0
+    };
+
+    MemberTripStats {
+        user_id,
+        point_count: trail.len(),
+        started_at: trail.first().map(|f| f.timestamp),
+        ended_at: trail.last().map(|f| f.timestamp),
+        total_distance_meters,
+        moving_seconds: moving_seconds.round() as i64,
+        stopped_seconds: stopped_seconds.round() as i64,
+        max_speed_mps,
+        average_speed_mps,
+        stops: detect_stop_clusters(trail),
+    }
+}
+
+/// Index ranges (`start..end`) of the trail where the member stayed within
+/// the stop cluster radius for at least the minimum stop duration
+pub fn stop_cluster_ranges(trail: &[location::Model]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start < trail.len() {
+        let anchor = &trail[start];
+        let mut end = start + 1;
+        while end < trail.len()
+            && haversine_distance(
+                anchor.latitude,
+                anchor.longitude,
+                trail[end].latitude,
+                trail[end].longitude,
+            ) <= STOP_CLUSTER_RADIUS_M
+        {
+            end += 1;
+        }
+
+        let duration_seconds = (trail[end - 1].timestamp - anchor.timestamp).num_seconds();
+        if duration_seconds >= STOP_CLUSTER_MIN_SECS {
+            ranges.push(start..end);
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    ranges
+}
+
+fn detect_stop_clusters(trail: &[location::Model]) -> Vec<StopCluster> {
+    stop_cluster_ranges(trail)
+        .into_iter()
+        .map(|range| {
+            let members = &trail[range];
+            let (first, last) = (&members[0], &members[members.len() - 1]);
+            let n = members.len() as f64;
+            StopCluster {
+                latitude: members.iter().map(|f| f.latitude).sum::<f64>() / n,
+                longitude: members.iter().map(|f| f.longitude).sum::<f64>() / n,
+                arrived_at: first.timestamp,
+                departed_at: last.timestamp,
+                duration_seconds: (last.timestamp - first.timestamp).num_seconds(),
+            }
+        })
+        .collect()
+}
+
+/// Omits the stops of members who are not sharing their location and snaps
+/// reduced-precision members' stops to their grid. Distance and timing
+/// statistics carry no position and are left as computed.
+fn share_stops(
+    summary: &mut TripSummary,
+    settings: &HashMap<Uuid, location_sharing::Model>,
+    now: DateTime<Utc>,
+) {
+    for member in &mut summary.members {
+        let Some(settings) = settings.get(&member.user_id) else {
+            continue;
+        };
+        if !settings.is_sharing(now) {
+            member.stops.clear();
+        } else if let Some(precision) = settings.precision_meters {
+            for stop in &mut member.stops {
+                (stop.latitude, stop.longitude) =
+                    snap_to_grid(stop.latitude, stop.longitude, precision);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn fix(user_id: Uuid, minutes: i64, latitude: f64, longitude: f64) -> location::Model {
+        location::Model {
+            id: Uuid::new_v4(),
+            user_id,
+            room_id: Uuid::nil(),
+            latitude,
+            longitude,
+            timestamp: DateTime::<Utc>::UNIX_EPOCH + Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_compute_member_stats() {
+        let user_id = Uuid::new_v4();
+        // Parked for 10 minutes, then ~11.1 km north in 10 minutes
+        let trail = vec![
+            fix(user_id, 0, 40.0, -105.0),
+            fix(user_id, 5, 40.0001, -105.0),
+            fix(user_id, 10, 40.0, -105.0001),
+            fix(user_id, 20, 40.1, -105.0001),
+        ];
+
+        let stats = compute_member_stats(user_id, &trail);
+
+        assert_eq!(stats.point_count, 4);
+        assert_eq!(stats.moving_seconds, 600);
+        assert_eq!(stats.stopped_seconds, 600);
+        assert!((stats.total_distance_meters - 11_120.0).abs() < 50.0);
+        assert!((stats.average_speed_mps - 18.5).abs() < 0.2);
+        assert_eq!(stats.stops.len(), 1);
+        assert_eq!(stats.stops[0].duration_seconds, 600);
+    }
+
+    #[test]
+    fn test_share_stops() {
+        let (exact, snapped, paused) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let trail = |user_id| {
+            vec![
+                fix(user_id, 0, 40.00001, -105.00001),
+                fix(user_id, 10, 40.00002, -105.00002),
+            ]
+        };
+        let mut summary = TripSummary {
+            room_id: Uuid::nil(),
+            archived: true,
+            computed_at: DateTime::<Utc>::UNIX_EPOCH,
+            members: [exact, snapped, paused]
+                .into_iter()
+                .map(|user_id| compute_member_stats(user_id, &trail(user_id)))
+                .collect(),
+        };
+        let setting = |user_id, paused, precision_meters| location_sharing::Model {
+            room_id: Uuid::nil(),
+            user_id,
+            paused,
+            precision_meters,
+            expires_at: None,
+            updated_at: DateTime::<Utc>::UNIX_EPOCH,
+        };
+        let settings = HashMap::from([
+            (snapped, setting(snapped, false, Some(1_000.0))),
+            (paused, setting(paused, true, None)),
+        ]);
+
+        share_stops(&mut summary, &settings, Utc::now());
+
+        let exact_stop = &summary.members[0].stops[0];
+        assert!((exact_stop.latitude - 40.000015).abs() < 1e-9);
+        let snapped_stop = &summary.members[1].stops[0];
+        assert_eq!(
+            (snapped_stop.latitude, snapped_stop.longitude),
+            snap_to_grid(exact_stop.latitude, exact_stop.longitude, 1_000.0)
+        );
+        assert!(summary.members[2].stops.is_empty());
+        assert_eq!(summary.members[2].point_count, 2);
+    }
+}
@@ -112,6 +112,14 @@ impl VoiceCallSignalingService {
             .ok_or_else(|| anyhow::anyhow!("Call not found"))
     }
 
+    pub async fn get_room_call(&self, room_id: Uuid, call_id: Uuid) -> Result<voice_call::Model> {
+        voice_call::Entity::find_by_id(call_id)
+            .filter(voice_call::Column::RoomId.eq(room_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Call not found"))
+    }
+
     pub async fn get_active_call_in_room(&self, room_id: Uuid) -> Result<Option<voice_call::Model>> {
         let call = voice_call::Entity::find()
             .filter(voice_call::Column::RoomId.eq(room_id))
@@ -1,18 +1,19 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        Extension, State, WebSocketUpgrade,
     },
     response::Response,
 };
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 use anyhow::Result;
 
+use crate::entities::user;
 use crate::routes::AppState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +38,10 @@ pub enum WebSocketEvent {
         text: Option<String>,
         image_url: Option<String>,
         message_type: String,
+        reply_to_id: Option<Uuid>,
+        payload: Option<serde_json::Value>,
+        /// Members mentioned with `@name`
+        mentions: Vec<Uuid>,
     },
     #[serde(rename = "user-joined")]
     UserJoined { room_id: Uuid, user_id: Uuid },
@@ -44,20 +49,275 @@ pub enum WebSocketEvent {
     UserLeft { room_id: Uuid, user_id: Uuid },
     #[serde(rename = "typing")]
     Typing { room_id: Uuid, user_id: Uuid },
+    #[serde(rename = "room-updated")]
+    RoomUpdated {
+        room_id: Uuid,
+        name: String,
+        description: Option<String>,
+        is_active: bool,
+        updated_by: Uuid,
+    },
+    /// Last event of a deleted room; its broadcaster is torn down afterwards
+    #[serde(rename = "room-deleted")]
+    RoomDeleted { room_id: Uuid, deleted_by: Uuid },
+    /// Sent only to the room's admins
+    #[serde(rename = "join-request")]
+    JoinRequested {
+        room_id: Uuid,
+        request_id: Uuid,
+        user_id: Uuid,
+        user_name: String,
+        created_at: chrono::DateTime<chrono::Utc>,
+    },
+    /// Sent to the requester and the room's admins
+    #[serde(rename = "join-request-decided")]
+    JoinRequestDecided {
+        room_id: Uuid,
+        request_id: Uuid,
+        user_id: Uuid,
+        status: String,
+        decided_by: Uuid,
+    },
+    #[serde(rename = "role-changed")]
+    RoleChanged {
+        room_id: Uuid,
+        user_id: Uuid,
+        role: String,
+    },
+    #[serde(rename = "read-receipt")]
+    ReadReceipt {
+        room_id: Uuid,
+        user_id: Uuid,
+        message_id: Uuid,
+        read_at: chrono::DateTime<chrono::Utc>,
+    },
+    #[serde(rename = "reaction-added")]
+    ReactionAdded {
+        room_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        emoji: String,
+        /// Members reacting with this emoji after the change
+        count: u64,
+    },
+    #[serde(rename = "reaction-removed")]
+    ReactionRemoved {
+        room_id: Uuid,
+        message_id: Uuid,
+        user_id: Uuid,
+        emoji: String,
+        count: u64,
+    },
+    #[serde(rename = "poll-updated")]
+    PollUpdated {
+        room_id: Uuid,
+        message_id: Uuid,
+        tallies: Vec<u64>,
+        total_voters: u64,
+        closed: bool,
+    },
+    #[serde(rename = "pins-changed")]
+    PinsChanged {
+        room_id: Uuid,
+        message_id: Uuid,
+        pinned: bool,
+        /// `None` for pins the server manages, e.g. SOS alerts
+        changed_by: Option<Uuid>,
+    },
+    #[serde(rename = "message-deleted")]
+    MessageDeleted {
+        room_id: Uuid,
+        message_id: Uuid,
+        deleted_by: Uuid,
+    },
+    #[serde(rename = "voice-call-ended")]
+    VoiceCallEnded {
+        room_id: Uuid,
+        call_id: Uuid,
+        ended_by: Uuid,
+    },
     #[serde(rename = "user-typing")]
     UserTyping { room_id: Uuid, user_id: Uuid },
+    #[serde(rename = "itinerary-updated")]
+    ItineraryUpdated {
+        room_id: Uuid,
+        user_id: Uuid,
+        stop_ids: Vec<Uuid>,
+    },
+    #[serde(rename = "stop-arrived")]
+    StopArrived {
+        room_id: Uuid,
+        stop_id: Uuid,
+        user_id: Uuid,
+        stop_name: String,
+        occurred_at: chrono::DateTime<chrono::Utc>,
+    },
+    #[serde(rename = "stop-departed")]
+    StopDeparted {
+        room_id: Uuid,
+        stop_id: Uuid,
+        user_id: Uuid,
+        stop_name: String,
+        occurred_at: chrono::DateTime<chrono::Utc>,
+    },
+    #[serde(rename = "convoy-separated")]
+    ConvoySeparated {
+        room_id: Uuid,
+        user_id: Uuid,
+        distance_meters: f64,
+        max_spread_meters: f64,
+    },
+    #[serde(rename = "convoy-regrouped")]
+    ConvoyRegrouped {
+        room_id: Uuid,
+        user_id: Uuid,
+        distance_meters: f64,
+        max_spread_meters: f64,
+    },
+    /// Re-sent periodically until a member acknowledges the alert
+    #[serde(rename = "sos")]
+    Sos {
+        room_id: Uuid,
+        sos_id: Uuid,
+        user_id: Uuid,
+        priority: String,
+        note: Option<String>,
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        located_at: Option<chrono::DateTime<chrono::Utc>>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    },
+    #[serde(rename = "sos-acknowledged")]
+    SosAcknowledged {
+        room_id: Uuid,
+        sos_id: Uuid,
+        acknowledged_by: Uuid,
+        acknowledged_at: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl WebSocketEvent {
+    pub fn new_message(message: &crate::entities::message::Model, mentions: &[Uuid]) -> Self {
+        WebSocketEvent::NewMessage {
+            room_id: message.room_id,
+            message_id: message.id,
+            user_id: message.user_id,
+            text: message.text.clone(),
+            image_url: message.image_url.clone(),
+            message_type: message.message_type.clone(),
+            reply_to_id: message.reply_to_id,
+            payload: message.payload.clone(),
+            mentions: mentions.to_vec(),
+        }
+    }
+
+    pub fn sos(alert: &crate::entities::sos_alert::Model) -> Self {
+        WebSocketEvent::Sos {
+            room_id: alert.room_id,
+            sos_id: alert.id,
+            user_id: alert.user_id,
+            priority: "high".to_string(),
+            note: alert.note.clone(),
+            latitude: alert.latitude,
+            longitude: alert.longitude,
+            located_at: alert.located_at,
+            created_at: alert.created_at,
+        }
+    }
 }
 
 type RoomBroadcaster = Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>>;
+type RoomReceivers = Arc<RwLock<HashMap<Uuid, broadcast::Receiver<String>>>>;
 
 pub struct WebSocketService {
     room_broadcasters: RoomBroadcaster,
+    /// Room subscriptions of every open connection, keyed by user then connection
+    user_connections: RwLock<HashMap<Uuid, HashMap<Uuid, RoomReceivers>>>,
+    /// Events addressed to a single user, delivered to all their connections
+    user_broadcasters: RwLock<HashMap<Uuid, broadcast::Sender<String>>>,
 }
 
 impl WebSocketService {
     pub fn new() -> Self {
         Self {
             room_broadcasters: Arc::new(RwLock::new(HashMap::new())),
+            user_connections: RwLock::new(HashMap::new()),
+            user_broadcasters: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Tracks a new connection and subscribes it to the user's own events
+    async fn register_connection(
+        &self,
+        user_id: Uuid,
+        connection_id: Uuid,
+        receivers: RoomReceivers,
+    ) -> broadcast::Receiver<String> {
+        self.user_connections
+            .write()
+            .await
+            .entry(user_id)
+            .or_default()
+            .insert(connection_id, receivers);
+
+        self.user_broadcasters
+            .write()
+            .await
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(100).0)
+            .subscribe()
+    }
+
+    async fn unregister_connection(&self, user_id: Uuid, connection_id: Uuid) {
+        let mut connections = self.user_connections.write().await;
+        if let Some(user_connections) = connections.get_mut(&user_id) {
+            user_connections.remove(&connection_id);
+            if user_connections.is_empty() {
+                connections.remove(&user_id);
+                self.user_broadcasters.write().await.remove(&user_id);
+            }
+        }
+    }
+
+    /// Sends an event to every open connection of one user, whatever rooms
+    /// they are subscribed to. Users without a connection miss the event.
+    pub async fn send_to_user(&self, user_id: Uuid, event: WebSocketEvent) -> Result<()> {
+        let sender = {
+            let broadcasters = self.user_broadcasters.read().await;
+            broadcasters.get(&user_id).cloned()
+        };
+
+        if let Some(sender) = sender {
+            let message = serde_json::to_string(&event)?;
+            let _ = sender.send(message);
+        }
+
+        Ok(())
+    }
+
+    /// The subset of `user_ids` with at least one open connection
+    pub async fn online_users(&self, user_ids: &[Uuid]) -> HashSet<Uuid> {
+        let connections = self.user_connections.read().await;
+        user_ids
+            .iter()
+            .filter(|user_id| connections.contains_key(user_id))
+            .copied()
+            .collect()
+    }
+
+    /// Drops the room subscription of every connection the user has open,
+    /// e.g. after they left or were removed from the room
+    pub async fn unsubscribe_user(&self, room_id: Uuid, user_id: Uuid) {
+        let receivers: Vec<RoomReceivers> = self
+            .user_connections
+            .read()
+            .await
+            .get(&user_id)
+            .map(|c| c.values().cloned().collect())
+            .unwrap_or_default();
+
+        for receivers in receivers {
+            receivers.write().await.remove(&room_id);
         }
     }
 
@@ -96,17 +356,24 @@ impl WebSocketService {
 
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
+    Extension(user): Extension<user::Model>,
 ) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket))
+    ws.on_upgrade(move |socket| handle_socket(socket, app_state, user.id))
 }
 
-async fn handle_socket(socket: WebSocket) {
+async fn handle_socket(socket: WebSocket, app_state: AppState, user_id: Uuid) {
+    let ws_service = app_state.websocket_service.clone();
     let (mut sender, mut receiver) = socket.split();
-    let room_receivers: Arc<RwLock<HashMap<Uuid, broadcast::Receiver<String>>>> = Arc::new(RwLock::new(HashMap::new()));
-    let ws_service = WebSocketService::new();
+    let room_receivers: RoomReceivers = Arc::new(RwLock::new(HashMap::new()));
     let room_receivers_rx = room_receivers.clone();
 
+    let connection_id = Uuid::new_v4();
+    let mut user_receiver = ws_service
+        .register_connection(user_id, connection_id, room_receivers.clone())
+        .await;
+    let rx_ws_service = ws_service.clone();
+
     let mut rx_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
             match msg {
@@ -114,7 +381,15 @@ async fn handle_socket(socket: WebSocket) {
                     if let Ok(event) = serde_json::from_str::<WebSocketEvent>(&text) {
                         match event {
                             WebSocketEvent::JoinRoom { room_id } => {
-                                let tx = ws_service.get_or_create_room_sender(room_id).await;
+                                // Only members may subscribe to a room's events
+                                let is_member = app_state.room_service
+                                    .is_member(room_id, user_id)
+                                    .await
+                                    .unwrap_or(false);
+                                if !is_member {
+                                    continue;
+                                }
+                                let tx = rx_ws_service.get_or_create_room_sender(room_id).await;
                                 let rx = tx.subscribe();
                                 room_receivers_rx.write().await.insert(room_id, rx);
                             }
@@ -136,14 +411,25 @@ async fn handle_socket(socket: WebSocket) {
         loop {
             tokio::select! {
                 _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)) => {
+                    while let Ok(message) = user_receiver.try_recv() {
+                        if sender.send(Message::Text(message.into())).await.is_err() {
+                            return;
+                        }
+                    }
+
                     let mut receivers = room_receivers_tx.write().await;
                     let mut to_remove = Vec::new();
                     for (room_id, receiver) in receivers.iter_mut() {
-                        if let Ok(message) = receiver.try_recv() {
-                            if sender.send(Message::Text(message.into())).await.is_err() {
-                                to_remove.push(*room_id);
-                                break;
+                        match receiver.try_recv() {
+                            Ok(message) => {
+                                if sender.send(Message::Text(message.into())).await.is_err() {
+                                    to_remove.push(*room_id);
+                                    break;
+                                }
                             }
+                            // The room was deleted
+                            Err(broadcast::error::TryRecvError::Closed) => to_remove.push(*room_id),
+                            Err(_) => {}
                         }
                     }
                     for room_id in to_remove {
@@ -158,4 +444,6 @@ async fn handle_socket(socket: WebSocket) {
         _ = rx_task => {}
         _ = tx_task => {}
     }
+
+    ws_service.unregister_connection(user_id, connection_id).await;
 }
@@ -0,0 +1,214 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Longest accepted reaction in bytes; enough for ZWJ sequences such as
+/// family or flag emoji
+const MAX_EMOJI_BYTES: usize = 32;
+
+/// Joins emoji into a single glyph, as in 👨‍👩‍👧
+const ZWJ: char = '\u{200D}';
+
+/// Requests emoji presentation for characters that default to text, as in ❤️
+const VS16: char = '\u{FE0F}';
+
+/// Combining enclosing keycap, as in 1️⃣
+const KEYCAP: char = '\u{20E3}';
+
+/// Skin tone modifiers 🏻 through 🏿
+const SKIN_TONES: (char, char) = ('\u{1F3FB}', '\u{1F3FF}');
+
+/// Regional indicator letters; a pair of them forms a flag
+const REGIONAL_INDICATORS: (char, char) = ('\u{1F1E6}', '\u{1F1FF}');
+
+/// Tag characters and their terminator, used by subdivision flags such as 🏴󠁧󠁢󠁳󠁣󠁴󠁿
+const TAGS: (char, char) = ('\u{E0020}', '\u{E007E}');
+const CANCEL_TAG: char = '\u{E007F}';
+
+/// Code points with the Unicode Extended_Pictographic property
+const EXTENDED_PICTOGRAPHIC: &[(char, char)] = &[
+    ('\u{00A9}', '\u{00A9}'),
+    ('\u{00AE}', '\u{00AE}'),
+    ('\u{203C}', '\u{203C}'),
+    ('\u{2049}', '\u{2049}'),
+    ('\u{2122}', '\u{2122}'),
+    ('\u{2139}', '\u{2139}'),
+    ('\u{2194}', '\u{2199}'),
+    ('\u{21A9}', '\u{21AA}'),
+    ('\u{231A}', '\u{231B}'),
+    ('\u{2328}', '\u{2328}'),
+    ('\u{2388}', '\u{2388}'),
+    ('\u{23CF}', '\u{23CF}'),
+    ('\u{23E9}', '\u{23F3}'),
+    ('\u{23F8}', '\u{23FA}'),
+    ('\u{24C2}', '\u{24C2}'),
+    ('\u{25AA}', '\u{25AB}'),
+    ('\u{25B6}', '\u{25B6}'),
+    ('\u{25C0}', '\u{25C0}'),
+    ('\u{25FB}', '\u{25FE}'),
+    ('\u{2600}', '\u{2605}'),
+    ('\u{2607}', '\u{2612}'),
+    ('\u{2614}', '\u{2685}'),
+    ('\u{2690}', '\u{2705}'),
+    ('\u{2708}', '\u{2712}'),
+    ('\u{2714}', '\u{2714}'),
+    ('\u{2716}', '\u{2716}'),
+    ('\u{271D}', '\u{271D}'),
+    ('\u{2721}', '\u{2721}'),
+    ('\u{2728}', '\u{2728}'),
+    ('\u{2733}', '\u{2734}'),
+    ('\u{2744}', '\u{2744}'),
+    ('\u{2747}', '\u{2747}'),
+    ('\u{274C}', '\u{274C}'),
+    ('\u{274E}', '\u{274E}'),
+    ('\u{2753}', '\u{2755}'),
+    ('\u{2757}', '\u{2757}'),
+    ('\u{2763}', '\u{2767}'),
+    ('\u{2795}', '\u{2797}'),
+    ('\u{27A1}', '\u{27A1}'),
+    ('\u{27B0}', '\u{27B0}'),
+    ('\u{27BF}', '\u{27BF}'),
+    ('\u{2934}', '\u{2935}'),
+    ('\u{2B05}', '\u{2B07}'),
+    ('\u{2B1B}', '\u{2B1C}'),
+    ('\u{2B50}', '\u{2B50}'),
+    ('\u{2B55}', '\u{2B55}'),
+    ('\u{3030}', '\u{3030}'),
+    ('\u{303D}', '\u{303D}'),
+    ('\u{3297}', '\u{3297}'),
+    ('\u{3299}', '\u{3299}'),
+    ('\u{1F000}', '\u{1F0FF}'),
+    ('\u{1F10D}', '\u{1F10F}'),
+    ('\u{1F12F}', '\u{1F12F}'),
+    ('\u{1F16C}', '\u{1F171}'),
+    ('\u{1F17E}', '\u{1F17F}'),
+    ('\u{1F18E}', '\u{1F18E}'),
+    ('\u{1F191}', '\u{1F19A}'),
+    ('\u{1F1AD}', '\u{1F1E5}'),
+    ('\u{1F201}', '\u{1F20F}'),
+    ('\u{1F21A}', '\u{1F21A}'),
+    ('\u{1F22F}', '\u{1F22F}'),
+    ('\u{1F232}', '\u{1F23A}'),
+    ('\u{1F23C}', '\u{1F23F}'),
+    ('\u{1F249}', '\u{1F3FA}'),
+    ('\u{1F400}', '\u{1F53D}'),
+    ('\u{1F546}', '\u{1F64F}'),
+    ('\u{1F680}', '\u{1F6FF}'),
+    ('\u{1F774}', '\u{1F77F}'),
+    ('\u{1F7D5}', '\u{1F7FF}'),
+    ('\u{1F80C}', '\u{1F80F}'),
+    ('\u{1F848}', '\u{1F84F}'),
+    ('\u{1F85A}', '\u{1F85F}'),
+    ('\u{1F888}', '\u{1F88F}'),
+    ('\u{1F8AE}', '\u{1F8FF}'),
+    ('\u{1F90C}', '\u{1F93A}'),
+    ('\u{1F93C}', '\u{1F945}'),
+    ('\u{1F947}', '\u{1FAFF}'),
+    ('\u{1FC00}', '\u{1FFFD}'),
+];
+
+/// Whether `input` is a single emoji usable as a reaction: a pictographic
+/// character with optional VS16, skin tone and tag sequence, a keycap, a
+/// flag, or several of those joined with ZWJ.
+pub fn is_valid_reaction(input: &str) -> bool {
+    if input.is_empty() || input.len() > MAX_EMOJI_BYTES {
+        return false;
+    }
+
+    let mut chars = input.chars().peekable();
+    loop {
+        if !emoji_element(&mut chars) {
+            return false;
+        }
+        match chars.next() {
+            None => return true,
+            Some(ZWJ) => continue,
+            Some(_) => return false,
+        }
+    }
+}
+
+/// Consumes one emoji element, returning `false` if the input doesn't start
+/// with one
+fn emoji_element(chars: &mut Peekable<Chars>) -> bool {
+    let Some(first) = chars.next() else {
+        return false;
+    };
+
+    if matches!(first, '0'..='9' | '#' | '*') {
+        chars.next_if_eq(&VS16);
+        return chars.next_if_eq(&KEYCAP).is_some();
+    }
+
+    if in_range(first, REGIONAL_INDICATORS) {
+        return chars.next_if(|&c| in_range(c, REGIONAL_INDICATORS)).is_some();
+    }
+
+    if !is_extended_pictographic(first) {
+        return false;
+    }
+    chars.next_if_eq(&VS16);
+    chars.next_if(|&c| in_range(c, SKIN_TONES));
+
+    if chars.peek().is_some_and(|&c| in_range(c, TAGS)) {
+        while chars.next_if(|&c| in_range(c, TAGS)).is_some() {}
+        return chars.next_if_eq(&CANCEL_TAG).is_some();
+    }
+
+    true
+}
+
+fn is_extended_pictographic(c: char) -> bool {
+    EXTENDED_PICTOGRAPHIC
+        .binary_search_by(|&(start, end)| {
+            if c < start {
+                std::cmp::Ordering::Greater
+            } else if c > end {
+                std::cmp::Ordering::Less
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+fn in_range(c: char, (start, end): (char, char)) -> bool {
+    (start..=end).contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_reaction() {
+        assert!(is_valid_reaction("👍"));
+        assert!(is_valid_reaction("❤️"));
+        assert!(is_valid_reaction("👨‍👩‍👧"));
+        assert!(is_valid_reaction("1️⃣"));
+        assert!(is_valid_reaction("#⃣"));
+        assert!(is_valid_reaction("👍🏽"));
+        assert!(is_valid_reaction("🏳️‍🌈"));
+        assert!(is_valid_reaction("🇳🇴"));
+        assert!(is_valid_reaction("🏴\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}"));
+
+        assert!(!is_valid_reaction(""));
+        assert!(!is_valid_reaction("lol"));
+        assert!(!is_valid_reaction("1"));
+        assert!(!is_valid_reaction("👍 👍"));
+        assert!(!is_valid_reaction("👍👍"));
+        assert!(!is_valid_reaction(&"👍".repeat(10)));
+        assert!(!is_valid_reaction("🇳"));
+        assert!(!is_valid_reaction("🏽"));
+        assert!(!is_valid_reaction("👍\u{200D}"));
+    }
+
+    #[test]
+    fn test_rejects_non_emoji_unicode() {
+        assert!(!is_valid_reaction("中文"));
+        assert!(!is_valid_reaction("é"));
+        assert!(!is_valid_reaction("—"));
+        assert!(!is_valid_reaction("ß"));
+        assert!(!is_valid_reaction("→"));
+        assert!(!is_valid_reaction("\u{FE0F}"));
+    }
+}
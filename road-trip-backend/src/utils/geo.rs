@@ -0,0 +1,164 @@
+/// Mean Earth radius in meters
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Great-circle distance in meters between two points using the haversine formula
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let phi1 = lat1.to_radians();
+    let phi2 = lat2.to_radians();
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let a = (d_phi / 2.0).sin().powi(2)
+        + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+
+    EARTH_RADIUS_M * c
+}
+
+/// Arithmetic mean of a set of (latitude, longitude) points. Good enough for
+/// the few-kilometer spread of a convoy; not meant for points spanning the
+/// antimeridian.
+pub fn centroid(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let (lat_sum, lon_sum) = points
+        .iter()
+        .fold((0.0, 0.0), |(lat, lon), p| (lat + p.0, lon + p.1));
+
+    Some((lat_sum / n, lon_sum / n))
+}
+
+/// Snaps a point to the center of a grid cell roughly `grid_m` meters on a
+/// side, hiding the exact position within the cell.
+pub fn snap_to_grid(lat: f64, lon: f64, grid_m: f64) -> (f64, f64) {
+    let meters_per_degree = EARTH_RADIUS_M.to_radians();
+    let lat_step = grid_m / meters_per_degree;
+    let snapped_lat = ((lat / lat_step).floor() + 0.5) * lat_step;
+
+    // Longitude cells are sized at the snapped latitude so every point in a
+    // latitude band shares the same grid
+    let cos_lat = snapped_lat.to_radians().cos().max(1e-6);
+    let lon_step = grid_m / (meters_per_degree * cos_lat);
+    let snapped_lon = ((lon / lon_step).floor() + 0.5) * lon_step;
+
+    (snapped_lat.clamp(-90.0, 90.0), snapped_lon)
+}
+
+/// Indices of the points kept by Douglas–Peucker simplification. Points are
+/// (latitude, longitude); `tolerance_m` is the maximum distance in meters a
+/// dropped point may lie from the simplified line. The first and last points
+/// are always kept.
+pub fn douglas_peucker(points: &[(f64, f64)], tolerance_m: f64) -> Vec<usize> {
+    if points.len() <= 2 {
+        return (0..points.len()).collect();
+    }
+
+    // Project onto a local plane in meters around the first point
+    let (lat0, lon0) = points[0];
+    let cos_lat0 = lat0.to_radians().cos();
+    let projected: Vec<(f64, f64)> = points
+        .iter()
+        .map(|(lat, lon)| {
+            (
+                (lon - lon0).to_radians() * cos_lat0 * EARTH_RADIUS_M,
+                (lat - lat0).to_radians() * EARTH_RADIUS_M,
+            )
+        })
+        .collect();
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut max_distance = 0.0;
+        let mut max_index = start;
+        for i in start + 1..end {
+            let d = segment_distance(projected[i], projected[start], projected[end]);
+            if d > max_distance {
+                max_distance = d;
+                max_index = i;
+            }
+        }
+
+        if max_distance > tolerance_m {
+            keep[max_index] = true;
+            stack.push((start, max_index));
+            stack.push((max_index, end));
+        }
+    }
+
+    keep.iter()
+        .enumerate()
+        .filter_map(|(i, k)| k.then_some(i))
+        .collect()
+}
+
+/// Distance from `p` to the segment `a`-`b` on a plane
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    if length_sq == 0.0 {
+        return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+    }
+
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0);
+    let (cx, cy) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine_distance() {
+        // Same point
+        assert_eq!(haversine_distance(40.0, -105.0, 40.0, -105.0), 0.0);
+
+        // One degree of latitude is roughly 111.2 km
+        let d = haversine_distance(40.0, -105.0, 41.0, -105.0);
+        assert!((d - 111_195.0).abs() < 100.0);
+
+        // Denver to Salt Lake City is roughly 600 km
+        let d = haversine_distance(39.7392, -104.9903, 40.7608, -111.8910);
+        assert!((d - 598_000.0).abs() < 5_000.0);
+    }
+
+    #[test]
+    fn test_centroid() {
+        assert_eq!(centroid(&[]), None);
+        assert_eq!(
+            centroid(&[(40.0, -105.0), (42.0, -107.0)]),
+            Some((41.0, -106.0))
+        );
+    }
+
+    #[test]
+    fn test_douglas_peucker() {
+        // Collinear points collapse to the endpoints
+        let line = [(40.0, -105.0), (40.001, -105.0), (40.002, -105.0), (40.003, -105.0)];
+        assert_eq!(douglas_peucker(&line, 5.0), vec![0, 3]);
+
+        // A ~111 m detour is kept at 50 m tolerance but dropped at 200 m
+        let detour = [(40.0, -105.0), (40.001, -104.9987), (40.002, -105.0)];
+        assert_eq!(douglas_peucker(&detour, 50.0), vec![0, 1, 2]);
+        assert_eq!(douglas_peucker(&detour, 200.0), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let (lat, lon) = snap_to_grid(40.01234, -105.04321, 1000.0);
+        assert!(haversine_distance(lat, lon, 40.01234, -105.04321) < 1000.0);
+
+        // Nearby points in the same cell snap to the same position
+        assert_eq!(
+            snap_to_grid(40.01234, -105.04321, 1000.0),
+            snap_to_grid(40.01240, -105.04330, 1000.0)
+        );
+    }
+}
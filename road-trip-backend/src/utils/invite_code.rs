@@ -0,0 +1,51 @@
+use rand::Rng;
+
+const INVITE_PREFIX: &str = "RTB-";
+
+/// Characters that can't be confused with each other when read aloud or
+/// typed from a screenshot (no 0/O or 1/I)
+const INVITE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+const INVITE_CODE_LEN: usize = 6;
+
+/// Random human-friendly invite code such as `RTB-7K2Q9M`
+pub fn generate_invite_code() -> String {
+    let mut rng = rand::thread_rng();
+    let suffix: String = (0..INVITE_CODE_LEN)
+        .map(|_| INVITE_ALPHABET[rng.gen_range(0..INVITE_ALPHABET.len())] as char)
+        .collect();
+
+    format!("{}{}", INVITE_PREFIX, suffix)
+}
+
+/// Canonical form of a user-entered code: trimmed, upper-cased and with the
+/// `RTB-` prefix added if it was left out
+pub fn normalize_invite_code(input: &str) -> String {
+    let code = input.trim().to_uppercase();
+    if code.starts_with(INVITE_PREFIX) {
+        code
+    } else {
+        format!("{}{}", INVITE_PREFIX, code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_invite_code() {
+        let code = generate_invite_code();
+        assert!(code.starts_with(INVITE_PREFIX));
+        assert_eq!(code.len(), INVITE_PREFIX.len() + INVITE_CODE_LEN);
+        assert!(code[INVITE_PREFIX.len()..]
+            .bytes()
+            .all(|c| INVITE_ALPHABET.contains(&c)));
+    }
+
+    #[test]
+    fn test_normalize_invite_code() {
+        assert_eq!(normalize_invite_code(" rtb-7k2q9m "), "RTB-7K2Q9M");
+        assert_eq!(normalize_invite_code("7k2q9m"), "RTB-7K2Q9M");
+    }
+}
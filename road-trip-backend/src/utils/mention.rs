@@ -0,0 +1,76 @@
+use uuid::Uuid;
+
+/// Members mentioned as `@name` in `text`, in order of first mention.
+/// Names match case-insensitively and may contain spaces; when several
+/// names match at one `@`, the longest wins. An `@` inside a word, as in an
+/// email address, is not a mention.
+pub fn find_mentions(text: &str, members: &[(Uuid, String)]) -> Vec<Uuid> {
+    let mut mentioned = Vec::new();
+    let mut previous = None;
+
+    for (i, c) in text.char_indices() {
+        let at_word_start = !previous.is_some_and(is_name_char);
+        previous = Some(c);
+        if c != '@' || !at_word_start {
+            continue;
+        }
+
+        let rest = &text[i + 1..];
+        let best = members
+            .iter()
+            .filter(|(_, name)| !name.is_empty())
+            .filter(|(_, name)| {
+                strip_prefix_ignore_case(rest, name)
+                    .is_some_and(|after| !after.chars().next().is_some_and(is_name_char))
+            })
+            .max_by_key(|(_, name)| name.chars().count());
+
+        if let Some((user_id, _)) = best {
+            if !mentioned.contains(user_id) {
+                mentioned.push(*user_id);
+            }
+        }
+    }
+
+    mentioned
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut chars = text.char_indices();
+    for expected in prefix.chars() {
+        let (_, actual) = chars.next()?;
+        if !actual.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+
+    let end = chars.next().map_or(text.len(), |(i, _)| i);
+    Some(&text[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_mentions() {
+        let (ana, ana_maria, bo) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let members = vec![
+            (ana, "Ana".to_string()),
+            (ana_maria, "Ana Maria".to_string()),
+            (bo, "Bo".to_string()),
+        ];
+
+        assert_eq!(find_mentions("@bo, meet @ana there", &members), vec![bo, ana]);
+        assert_eq!(find_mentions("thanks @Ana Maria!", &members), vec![ana_maria]);
+        assert_eq!(find_mentions("@bo @BO", &members), vec![bo]);
+
+        // Not a mention: inside an email address, or a longer word
+        assert!(find_mentions("mail ana@bo.com", &members).is_empty());
+        assert!(find_mentions("@bob", &members).is_empty());
+    }
+}
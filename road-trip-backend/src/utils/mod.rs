@@ -1,3 +1,7 @@
 pub mod cookie;
+pub mod geo;
 pub mod password;
-pub mod response;
\ No newline at end of file
+pub mod response;
+pub mod invite_code;
+pub mod emoji;
+pub mod mention;